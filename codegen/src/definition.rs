@@ -19,54 +19,181 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
+use std::{
+    collections::HashMap,
+    iter,
+};
+
 use itertools::Itertools;
-use proc_macro2::TokenStream;
-use quote::quote;
+use proc_macro2::{
+    Ident,
+    TokenStream,
+};
+use quote::{
+    format_ident,
+    quote,
+};
 use syn::{
     parse2,
     parse_quote,
+    parse_str,
+    visit_mut::{
+        self,
+        VisitMut,
+    },
+    Attribute,
     Error,
+    File,
     FnArg,
+    Item,
     ItemTrait,
     Lit,
     Meta,
+    MetaNameValue,
     NestedMeta,
+    Path,
     ReturnType,
+    TraitBound,
+    TraitBoundModifier,
     TraitItem,
     TraitItemMethod,
+    TraitItemType,
+    Type,
+    TypeParamBound,
+    TypePath,
+    TypeReference,
+    TypeSlice,
 };
 
 use crate::{
+    extension,
     format_err_spanned,
     types::AttributeArgs,
     utils::{
+        find_id,
+        find_legacy_ids,
         into_u16,
         into_u32,
+        to_upper_camel_case,
         AttributeParser,
+        LitOrPath,
+        MetaUtils,
     },
 };
 
 struct TraitAttrs {
     id: u16,
+    version: u16,
+    id_from_signature: bool,
+    ink_environment: Type,
+    ink_struct: Option<Ident>,
+    call_derive: Vec<Path>,
 }
 
 impl TraitAttrs {
-    fn new<'a, I: IntoIterator<Item = &'a NestedMeta>>(trait_item: &ItemTrait, iter: I) -> Result<Self, Error> {
-        let id = find_id(iter)?.unwrap_or_else(|| into_u16(&trait_item.ident));
+    fn new<'a, I: IntoIterator<Item = &'a NestedMeta> + Clone>(trait_item: &ItemTrait, iter: I) -> Result<Self, Error> {
+        let id = find_id(iter.clone())?.unwrap_or_else(|| into_u16(&trait_item.ident));
+        let version = find_version(iter.clone())?.unwrap_or(1);
+        let id_from_signature = iter
+            .clone()
+            .into_iter()
+            .any(|arg| matches!(arg, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("id_from_signature")));
+
+        let ink_environment = match iter.clone().into_iter().find_by_name("ink_environment") {
+            Some((LitOrPath::Lit(Lit::Str(environment)), _)) => parse_str(&environment.value())?,
+            Some((_, ident)) => {
+                return Err(format_err_spanned!(
+                    ident,
+                    "`ink_environment` attribute should contain a path to the `ink::env::Environment` implementor"
+                ))
+            }
+            None => parse_quote!(::obce::ink_lang::env::DefaultEnvironment),
+        };
+
+        let ink_struct = match iter.clone().into_iter().find_by_name("ink_struct") {
+            Some((LitOrPath::Lit(Lit::Str(ink_struct)), _)) => Some(ink_struct.parse()?),
+            Some((_, ident)) => {
+                return Err(format_err_spanned!(
+                    ident,
+                    "`ink_struct` attribute should contain a name of the generated struct"
+                ))
+            }
+            None => None,
+        };
+
+        if let (Some(ink_struct), false) = (&ink_struct, trait_item.generics.params.is_empty()) {
+            return Err(format_err_spanned!(
+                ink_struct,
+                "`ink_struct` is not supported for generic definitions"
+            ))
+        }
+
+        let call_derive_list = iter.into_iter().find_map(|arg| {
+            match arg {
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("call_derive") => Some(list),
+                _ => None,
+            }
+        });
 
-        Ok(Self { id })
+        let call_derive = match call_derive_list {
+            Some(list) if !trait_item.generics.params.is_empty() => {
+                return Err(format_err_spanned!(
+                    list,
+                    "`call_derive` is not supported for generic definitions"
+                ))
+            }
+            Some(list) => {
+                list.nested
+                    .iter()
+                    .map(|nested| {
+                        match nested {
+                            NestedMeta::Meta(Meta::Path(path)) => Ok(path.clone()),
+                            _ => {
+                                Err(format_err_spanned!(
+                                    nested,
+                                    "`call_derive` should contain a list of traits to derive"
+                                ))
+                            }
+                        }
+                    })
+                    .try_collect()?
+            }
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            id,
+            version,
+            id_from_signature,
+            ink_environment,
+            ink_struct,
+            call_derive,
+        })
     }
 }
 
+#[derive(Clone)]
 struct Method {
+    name: Ident,
     id: u16,
+    signature: Option<String>,
+    legacy_ids: Vec<u16>,
     hash: u32,
+    args: Vec<String>,
+    mutable: bool,
+    docs: Vec<String>,
+    /// Argument types as written in the definition, which are encoded by ink! smart contracts.
+    arg_tys: Vec<Type>,
+    /// Owned argument types, which are decoded by the chain extension.
+    input_tys: Vec<Type>,
+    /// Owned argument types declared for the Substrate side with `#[obce(owned = ...)]`.
+    runtime_input_tys: Vec<Type>,
     input_tokens: TokenStream,
-    output_tokens: TokenStream,
+    output_ty: Type,
 }
 
 impl Method {
-    fn new(method_item: &mut TraitItemMethod) -> Result<Self, Error> {
+    fn new(trait_attrs: &TraitAttrs, method_item: &mut TraitItemMethod) -> Result<Self, Error> {
         if let Some(default) = &method_item.default {
             return Err(format_err_spanned!(
                 default,
@@ -78,50 +205,181 @@ impl Method {
 
         method_item.attrs = other_attrs;
 
-        let id = find_id(obce_attrs.iter())
-            .transpose()
-            .unwrap_or_else(|| Ok(into_u16(&method_item.sig.ident)))?;
+        // Identifiers set explicitly, either as numbers or as strings, are never computed from signatures.
+        let explicit_id = find_id(obce_attrs.iter())?;
+        let signature = (explicit_id.is_none() && trait_attrs.id_from_signature).then(|| signature(method_item));
+
+        let id = match (explicit_id, &signature) {
+            (Some(id), _) => id,
+            (None, Some(signature)) => into_u16(signature),
+            (None, None) => into_u16(&method_item.sig.ident),
+        };
+
+        let legacy_ids = find_legacy_ids(obce_attrs.iter())?;
 
         let hash = into_u32(&method_item.sig.ident);
 
-        let input_tys = method_item.sig.inputs.iter().filter_map(|input| {
+        let args = method_item
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|input| {
+                if let FnArg::Typed(pat) = input {
+                    let pat = &pat.pat;
+                    Some(quote!(#pat).to_string())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let mutable = method_item.sig.inputs.iter().any(|input| {
+            matches!(input, FnArg::Receiver(receiver) if receiver.reference.is_some() && receiver.mutability.is_some())
+        });
+
+        let docs = method_item
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("doc"))
+            .filter_map(|attr| {
+                if let Ok(Meta::NameValue(MetaNameValue { lit: Lit::Str(doc), .. })) = attr.parse_meta() {
+                    let doc = doc.value();
+                    Some(doc.strip_prefix(' ').unwrap_or(&doc).to_string())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Identifiers computed from signatures are documented on the method itself,
+        // so that they can be looked up in generated docs without running any code.
+        if let Some(signature) = &signature {
+            if !docs.is_empty() {
+                method_item.attrs.push(parse_quote!(#[doc = ""]));
+            }
+
+            let doc =
+                format!(" Chain extension method identifier `{id:#06x}` is computed from `{signature}` signature.");
+            method_item.attrs.push(parse_quote!(#[doc = #doc]));
+        }
+
+        let mut arg_tys = vec![];
+        let mut input_tys = vec![];
+        let mut runtime_input_tys = vec![];
+
+        for input in method_item.sig.inputs.iter_mut() {
             if let FnArg::Typed(pat) = input {
-                Some(&*pat.ty)
-            } else {
-                None
+                let (obce_attrs, other_attrs) = pat.attrs.iter().cloned().split_attrs()?;
+
+                pat.attrs = other_attrs;
+
+                let input_ty = owned_type(&pat.ty);
+
+                let runtime_input_ty = match find_type(&obce_attrs, "owned")? {
+                    Some(_) if input_ty.is_none() => {
+                        return Err(format_err_spanned!(
+                            pat.ty,
+                            "`owned` attribute is only supported for borrowed arguments"
+                        ))
+                    }
+                    Some(ty) => Some(ty),
+                    None => input_ty.clone(),
+                };
+
+                arg_tys.push((*pat.ty).clone());
+                input_tys.push(input_ty.unwrap_or_else(|| (*pat.ty).clone()));
+                runtime_input_tys.push(runtime_input_ty.unwrap_or_else(|| (*pat.ty).clone()));
             }
-        });
+        }
 
-        let output_tokens = if let ReturnType::Type(_, ty) = &method_item.sig.output {
-            quote!(#ty)
+        let output_ty = if let ReturnType::Type(_, ty) = &method_item.sig.output {
+            (**ty).clone()
         } else {
-            quote!(())
+            parse_quote!(())
         };
 
         Ok(Self {
+            name: method_item.sig.ident.clone(),
             id,
+            signature,
+            legacy_ids,
             hash,
+            args,
+            mutable,
+            docs,
+            arg_tys,
             input_tokens: quote! {
                 (#(#input_tys),*)
             },
-            output_tokens,
+            input_tys,
+            runtime_input_tys,
+            output_ty,
         })
     }
 
+    /// Whether the Substrate side decodes some of the arguments into different types.
+    fn has_runtime_input_tys(&self) -> bool {
+        self.input_tys != self.runtime_input_tys
+    }
+
+    fn map_types(&mut self, side: Side, mapping: &mut TypeMapping) {
+        if let Side::Substrate = side {
+            self.input_tys = self.runtime_input_tys.clone();
+        }
+
+        self.arg_tys.iter_mut().for_each(|ty| mapping.visit_type_mut(ty));
+        self.input_tys.iter_mut().for_each(|ty| mapping.visit_type_mut(ty));
+        mapping.visit_type_mut(&mut self.output_ty);
+
+        let input_tys = &self.input_tys;
+        self.input_tokens = quote! {
+            (#(#input_tys),*)
+        };
+    }
+
+    fn fill_with_substrate_data(&self, method_item: &mut TraitItemMethod) {
+        let inputs =
+            method_item.sig.inputs.iter_mut().filter_map(
+                |input| {
+                    if let FnArg::Typed(pat) = input {
+                        Some(pat)
+                    } else {
+                        None
+                    }
+                },
+            );
+
+        for (pat, input_ty) in inputs.zip(&self.input_tys) {
+            *pat.ty = input_ty.clone();
+        }
+    }
+
     fn fill_with_ink_data(&self, trait_attrs: &TraitAttrs, method_item: &mut TraitItemMethod) {
         let Method {
-            id,
-            input_tokens,
-            output_tokens,
-            ..
+            id, arg_tys, output_ty, ..
         } = self;
 
-        let input_bound = parse_quote! {
-            #input_tokens: ::scale::Encode
+        let arg_tokens = quote! {
+            (#(#arg_tys),*)
+        };
+
+        // Lifetimes of borrowed arguments can't be elided in bounds.
+        let mut lifetimes = ArgLifetimes(false);
+        let mut bounded_arg_tys = arg_tys.clone();
+        bounded_arg_tys.iter_mut().for_each(|ty| lifetimes.visit_type_mut(ty));
+
+        let input_bound = if lifetimes.0 {
+            parse_quote! {
+                for<'__obce_arg> (#(#bounded_arg_tys),*): ::scale::Encode
+            }
+        } else {
+            parse_quote! {
+                #arg_tokens: ::scale::Encode
+            }
         };
 
         let output_bound = parse_quote! {
-            #output_tokens: ::scale::Decode
+            #output_ty: ::scale::Decode
         };
 
         if let Some(where_clause) = &mut method_item.sig.generics.where_clause {
@@ -144,108 +402,927 @@ impl Method {
         // https://paritytech.github.io/substrate/master/pallet_contracts/chain_extension/trait.RegisteredChainExtension.html
         let trait_id = (trait_attrs.id as u32) << 16;
         let id_for_call = trait_id | (*id as u32);
+        let ink_environment = &trait_attrs.ink_environment;
 
         method_item.default = Some(parse_quote! {{
+            ::obce::ink_lang::capture_env::<#ink_environment>();
+
             ::obce::ink_lang::env::chain_extension::ChainExtensionMethod::build(#id_for_call)
-                .input::<#input_tokens>()
-                .output::<#output_tokens, false>()
+                .input::<#arg_tokens>()
+                .output::<#output_ty, false>()
                 .ignore_error_code()
                 .call(&(#(#input_bindings),*))
         }});
     }
 }
 
+/// Whether the method is an ink!-only helper marked with `#[obce(ink_only)]`,
+/// which is available to ink! smart contracts, but isn't a chain extension method.
+fn is_ink_only(method_item: &mut TraitItemMethod) -> Result<bool, Error> {
+    let (obce_attrs, other_attrs) = method_item.attrs.iter().cloned().split_attrs()?;
+
+    if obce_attrs.iter().find_by_name("ink_only").is_none() {
+        return Ok(false)
+    }
+
+    if method_item.default.is_none() {
+        return Err(format_err_spanned!(
+            method_item.sig,
+            "ink!-only methods should have a default implementation"
+        ))
+    }
+
+    method_item.attrs = other_attrs;
+
+    Ok(true)
+}
+
+/// Associated type of a definition, which is resolved to a concrete type on each side.
+struct AssociatedType {
+    name: Ident,
+    ink: Type,
+    substrate: Type,
+}
+
+impl AssociatedType {
+    fn new(trait_attrs: &TraitAttrs, type_item: &TraitItemType) -> Result<Self, Error> {
+        if !type_item.generics.params.is_empty() || type_item.generics.where_clause.is_some() {
+            return Err(format_err_spanned!(
+                type_item.generics,
+                "generic associated types are not supported in chain extensions"
+            ))
+        }
+
+        if !type_item.bounds.is_empty() {
+            return Err(format_err_spanned!(
+                type_item.bounds,
+                "bounds on associated types are not supported in chain extensions"
+            ))
+        }
+
+        if let Some((_, default)) = &type_item.default {
+            return Err(format_err_spanned!(
+                default,
+                "default associated types are not supported in chain extensions, use `#[obce(ink = ..., substrate = ...)]` instead"
+            ))
+        }
+
+        let (obce_attrs, _) = type_item.attrs.iter().split_attrs()?;
+
+        let name = &type_item.ident;
+        let ink_environment = &trait_attrs.ink_environment;
+
+        let ink = match find_type(&obce_attrs, "ink")? {
+            Some(ty) => ty,
+            None if INK_ENVIRONMENT_TYPES.iter().any(|ty| name == ty) => {
+                parse_quote!(<#ink_environment as ::obce::ink_lang::env::Environment>::#name)
+            }
+            None => {
+                return Err(format_err_spanned!(
+                    type_item,
+                    "`{name}` is not a type of `ink::env::Environment`, specify it with `#[obce(ink = ...)]`"
+                ))
+            }
+        };
+
+        let substrate = match find_type(&obce_attrs, "substrate")? {
+            Some(ty) => ty,
+            None => {
+                runtime_type(name).ok_or_else(|| {
+                    format_err_spanned!(
+                        type_item,
+                        "`{name}` has no default runtime type, specify it with `#[obce(substrate = ...)]`"
+                    )
+                })?
+            }
+        };
+
+        Ok(Self {
+            name: name.clone(),
+            ink,
+            substrate,
+        })
+    }
+}
+
+/// Associated types that have a default ink! type, taken from `ink::env::Environment`.
+const INK_ENVIRONMENT_TYPES: &[&str] = &["AccountId", "Balance", "Hash", "Timestamp", "BlockNumber"];
+
+/// Default runtime type of an associated type, taken from `frame_system::Config`
+/// or, in case of `Balance`, from `pallet_contracts::Config`.
+fn runtime_type(name: &Ident) -> Option<Type> {
+    match name.to_string().as_str() {
+        "AccountId" | "Hash" | "BlockNumber" => {
+            Some(parse_quote!(<T as ::obce::substrate::frame_system::Config>::#name))
+        }
+        "Balance" => Some(parse_quote!(::obce::substrate::BalanceOf<T>)),
+        _ => None,
+    }
+}
+
+fn find_type(attrs: &[NestedMeta], name: &str) -> Result<Option<Type>, Error> {
+    match attrs.iter().find_by_name(name) {
+        Some((LitOrPath::Lit(Lit::Str(ty)), _)) => Ok(Some(parse_str(&ty.value())?)),
+        Some((_, ident)) => Err(format_err_spanned!(ident, "`{name}` attribute should contain a type")),
+        None => Ok(None),
+    }
+}
+
+/// Owned counterpart of a borrowed argument type, which has the same encoding.
+///
+/// `&[T]` is decoded as `Vec<T>`, `&str` as `String` and `&T` as `T`.
+fn owned_type(ty: &Type) -> Option<Type> {
+    let Type::Reference(TypeReference {
+        mutability: None, elem, ..
+    }) = ty
+    else {
+        return None
+    };
+
+    Some(match &**elem {
+        Type::Slice(TypeSlice { elem, .. }) => parse_quote!(::scale::alloc::vec::Vec<#elem>),
+        Type::Path(TypePath { qself: None, path }) if path.is_ident("str") => {
+            parse_quote!(::scale::alloc::string::String)
+        }
+        elem => elem.clone(),
+    })
+}
+
+/// Names elided lifetimes of borrowed arguments, recording whether any were found.
+struct ArgLifetimes(bool);
+
+impl VisitMut for ArgLifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+        if reference.lifetime.is_none() {
+            reference.lifetime = Some(parse_quote!('__obce_arg));
+            self.0 = true;
+        }
+
+        visit_mut::visit_type_reference_mut(self, reference);
+    }
+}
+
+/// Replaces `Self::Name` paths of associated types with types of a particular side.
+struct TypeMapping(HashMap<Ident, Type>);
+
+impl VisitMut for TypeMapping {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(TypePath { qself: None, path }) = ty {
+            let mapped = match (path.segments.first(), path.segments.last()) {
+                (Some(first), Some(last)) if path.segments.len() == 2 && first.ident == "Self" => {
+                    self.0.get(&last.ident)
+                }
+                _ => None,
+            };
+
+            if let Some(mapped) = mapped {
+                *ty = mapped.clone();
+                return
+            }
+        }
+
+        visit_mut::visit_type_mut(self, ty);
+    }
+}
+
+/// Side of the chain extension that the generated code is emitted for.
+///
+/// Definitions without associated types share the generated code between both sides.
+#[derive(Clone, Copy)]
+enum Side {
+    Any,
+    Ink,
+    Substrate,
+}
+
 pub fn generate(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, Error> {
     let mut trait_item: ItemTrait = parse2(input)?;
 
     let trait_attrs = TraitAttrs::new(&trait_item, parse2::<AttributeArgs>(attrs)?.iter())?;
 
-    let trait_id = trait_attrs.id;
-    let trait_name = &trait_item.ident;
-
-    let (impls, types, where_clause) = trait_item.generics.split_for_impl();
+    let mut methods = vec![];
+    let mut associated_types = vec![];
 
-    let methods: Vec<_> = trait_item
-        .items
-        .iter_mut()
-        .map(|item| {
-            if let TraitItem::Method(method) = item {
-                Method::new(method)
-            } else {
-                Err(format_err_spanned!(
+    for item in trait_item.items.iter_mut() {
+        match item {
+            TraitItem::Method(method) => {
+                if !is_ink_only(method)? {
+                    methods.push(Method::new(&trait_attrs, method)?);
+                }
+            }
+            TraitItem::Type(type_item) => associated_types.push(AssociatedType::new(&trait_attrs, type_item)?),
+            _ => {
+                return Err(format_err_spanned!(
                     item,
-                    "only methods are supported in trait definitions"
+                    "only methods and associated types are supported in trait definitions"
                 ))
             }
-        })
-        .try_collect()?;
+        }
+    }
+
+    trait_item.items.retain(|item| matches!(item, TraitItem::Method(_)));
 
-    if let Some(id) = methods.iter().map(|Method { id, .. }| id).duplicates().next() {
+    if let Some(id) = methods
+        .iter()
+        .flat_map(|Method { id, legacy_ids, .. }| iter::once(id).chain(legacy_ids))
+        .duplicates()
+        .next()
+    {
         return Err(format_err_spanned!(
             trait_item,
             "found duplicated method identifier: {id}",
         ))
     }
 
+    if associated_types.is_empty() && !methods.iter().any(Method::has_runtime_input_tys) {
+        return expand(trait_item, &trait_attrs, &methods, Side::Any)
+    }
+
+    let generic_over_runtime = !associated_types.is_empty();
+
+    // Method identifiers are computed above from signatures as written in the definition,
+    // so that they are the same on both sides.
+    let (ink_types, substrate_types) = associated_types
+        .into_iter()
+        .map(|AssociatedType { name, ink, substrate }| ((name.clone(), ink), (name, substrate)))
+        .unzip();
+
+    let (ink_item, ink_methods) = with_mapped_types(&trait_item, &methods, Side::Ink, TypeMapping(ink_types));
+    let (mut substrate_item, substrate_methods) =
+        with_mapped_types(&trait_item, &methods, Side::Substrate, TypeMapping(substrate_types));
+
+    // Runtime types of associated types are resolved with the runtime configuration.
+    if generic_over_runtime {
+        substrate_item
+            .generics
+            .params
+            .push(parse_quote!(T: ::obce::substrate::pallet_contracts::Config));
+    }
+
+    let ink = expand(ink_item, &trait_attrs, &ink_methods, Side::Ink)?;
+    let substrate = expand(substrate_item, &trait_attrs, &substrate_methods, Side::Substrate)?;
+
+    Ok(quote! {
+        #ink
+
+        #substrate
+    })
+}
+
+fn with_mapped_types(
+    trait_item: &ItemTrait,
+    methods: &[Method],
+    side: Side,
+    mut mapping: TypeMapping,
+) -> (ItemTrait, Vec<Method>) {
+    let mut trait_item = trait_item.clone();
+    mapping.visit_item_trait_mut(&mut trait_item);
+
+    let methods = methods
+        .iter()
+        .cloned()
+        .map(|mut method| {
+            method.map_types(side, &mut mapping);
+            method
+        })
+        .collect();
+
+    (trait_item, methods)
+}
+
+fn expand(
+    trait_item: ItemTrait,
+    trait_attrs: &TraitAttrs,
+    methods: &[Method],
+    side: Side,
+) -> Result<TokenStream, Error> {
+    let trait_id = trait_attrs.id;
+    let trait_version = trait_attrs.version;
+    let trait_name = &trait_item.ident;
+    let trait_name_str = trait_name.to_string();
+
+    let (impls, types, where_clause) = trait_item.generics.split_for_impl();
+
     let method_descriptions = methods.iter().map(
         |Method {
              id,
+             legacy_ids,
              hash,
              input_tokens,
-             output_tokens,
+             output_ty,
              ..
          }| {
             quote! {
                 impl #impls ::obce::codegen::MethodDescription<#hash> for dyn #trait_name #types #where_clause {
                     const ID: ::core::primitive::u16 = #id;
+                    const LEGACY_IDS: &'static [::core::primitive::u16] = &[#(#legacy_ids),*];
                     type Input = #input_tokens;
-                    type Output = #output_tokens;
+                    type Output = #output_ty;
                 }
             }
         },
     );
 
-    let mut ink_trait_item = trait_item.clone();
+    let method_infos = methods.iter().map(
+        |Method {
+             name,
+             id,
+             legacy_ids,
+             hash,
+             args,
+             mutable,
+             docs,
+             signature,
+             ..
+         }| {
+            let name = name.to_string();
 
-    ink_trait_item
-        .items
-        .iter_mut()
-        .zip(methods.iter())
-        .for_each(|(item, method)| {
-            if let TraitItem::Method(method_item) = item {
-                method.fill_with_ink_data(&trait_attrs, method_item);
+            let signature = if let Some(signature) = signature {
+                quote! { ::core::option::Option::Some(#signature) }
             } else {
-                // This branch is unreachable, because `ink_trait_item`
-                // is cloned from the `trait_item`, items of which are verified
-                // to be method above.
-                unreachable!("only methods are present here")
+                quote! { ::core::option::Option::None }
+            };
+
+            quote! {
+                ::obce::codegen::MethodInfo {
+                    name: #name,
+                    id: #id,
+                    legacy_ids: &[#(#legacy_ids),*],
+                    hash: #hash,
+                    args: &[#(#args),*],
+                    mutable: #mutable,
+                    docs: &[#(#docs),*],
+                    signature: #signature,
+                }
             }
-        });
+        },
+    );
 
-    Ok(quote! {
+    let input_types = methods.iter().map(|Method { hash, input_tokens, .. }| {
+        quote! {
+            #hash => ::obce::meta_type!(#input_tokens),
+        }
+    });
+
+    let output_types = methods.iter().map(|Method { hash, output_ty, .. }| {
+        quote! {
+            #hash => ::obce::meta_type!(#output_ty),
+        }
+    });
+
+    let ret_vals = methods.iter().map(|Method { hash, output_ty, .. }| {
+        quote! {
+            #hash => ::obce::ret_vals!(#output_ty),
+        }
+    });
+
+    let default_outputs = methods.iter().map(|Method { hash, output_ty, .. }| {
+        quote! {
+            #hash => ::obce::default_output!(#output_ty),
+        }
+    });
+
+    let supertraits: Vec<_> = trait_item
+        .supertraits
+        .iter()
+        .filter_map(|bound| {
+            match bound {
+                TypeParamBound::Trait(TraitBound {
+                    modifier: TraitBoundModifier::None,
+                    path,
+                    ..
+                }) => Some(path),
+                _ => None,
+            }
+        })
+        .collect();
+
+    let for_each_method = (!supertraits.is_empty()).then(|| {
+        quote! {
+            fn for_each_method(
+                f: &mut dyn FnMut(::core::primitive::u16, &'static ::obce::codegen::MethodInfo)
+            ) {
+                for method in <Self as ::obce::codegen::ExtensionDescription>::METHODS {
+                    f(<Self as ::obce::codegen::ExtensionDescription>::ID, method);
+                }
+
+                #(<dyn #supertraits as ::obce::codegen::ExtensionDescription>::for_each_method(f);)*
+            }
+        }
+    });
+
+    let supertraits_impls = supertraits_impls(&trait_item, trait_attrs, methods, &supertraits);
+
+    let call_enum = (trait_item.generics.params.is_empty())
+        .then(|| call_enum(&trait_item, trait_id, &trait_attrs.call_derive, methods, side));
+
+    // ink!-only helpers don't have corresponding chain extension methods.
+    let method_of = |item: &TraitItem| {
+        if let TraitItem::Method(method_item) = item {
+            methods.iter().find(|method| method.name == method_item.sig.ident)
+        } else {
+            None
+        }
+    };
+
+    let mut substrate_trait_item = trait_item.clone();
+
+    substrate_trait_item.items.retain(|item| method_of(item).is_some());
+
+    substrate_trait_item.items.iter_mut().for_each(|item| {
+        if let (Some(method), TraitItem::Method(method_item)) = (method_of(item), &mut *item) {
+            method.fill_with_substrate_data(method_item);
+        }
+    });
+
+    let mut ink_trait_item = trait_item.clone();
+
+    ink_trait_item.items.iter_mut().for_each(|item| {
+        if let (Some(method), TraitItem::Method(method_item)) = (method_of(item), &mut *item) {
+            method.fill_with_ink_data(trait_attrs, method_item);
+        }
+    });
+
+    let substrate_trait_item = (!matches!(side, Side::Ink)).then(|| {
+        quote! {
+            #[cfg(feature = "substrate")]
+            #substrate_trait_item
+        }
+    });
+
+    // With both features enabled, only the Substrate trait is available,
+    // which allows implementations to be tested together with ink! smart contracts.
+    let ink_trait_item = (!matches!(side, Side::Substrate)).then(|| {
+        quote! {
+            #[cfg(all(feature = "ink", not(feature = "substrate")))]
+            #ink_trait_item
+        }
+    });
+
+    let ink_struct = match (&trait_attrs.ink_struct, side) {
+        (Some(ink_struct), Side::Any | Side::Ink) => Some(self::ink_struct(&trait_item, ink_struct)?),
+        _ => None,
+    };
+
+    let expanded = quote! {
         impl #impls ::obce::codegen::ExtensionDescription for dyn #trait_name #types #where_clause {
+            const NAME: &'static ::core::primitive::str = #trait_name_str;
             const ID: ::core::primitive::u16 = #trait_id;
+            const VERSION: ::core::primitive::u16 = #trait_version;
+            const METHODS: &'static [::obce::codegen::MethodInfo] = &[#(#method_infos),*];
+
+            #for_each_method
         }
 
         #(#method_descriptions)*
 
-        #[cfg(feature = "substrate")]
-        #trait_item
+        #[cfg(feature = "std")]
+        impl #impls ::obce::codegen::MethodTypes for dyn #trait_name #types #where_clause {
+            fn input_type(hash: ::core::primitive::u32) -> Option<::obce::codegen::MetaType> {
+                match hash {
+                    #(#input_types)*
+                    _ => None,
+                }
+            }
+
+            fn output_type(hash: ::core::primitive::u32) -> Option<::obce::codegen::MetaType> {
+                match hash {
+                    #(#output_types)*
+                    _ => None,
+                }
+            }
+
+            fn ret_vals(hash: ::core::primitive::u32) -> &'static [::obce::codegen::RetValInfo] {
+                match hash {
+                    #(#ret_vals)*
+                    _ => &[],
+                }
+            }
+        }
 
         #[cfg(feature = "ink")]
+        impl #impls ::obce::ink_lang::DefaultOutputs for dyn #trait_name #types #where_clause {
+            fn default_output(hash: ::core::primitive::u32) -> Option<::obce::ink_lang::prelude::vec::Vec<u8>> {
+                let output = match hash {
+                    #(#default_outputs)*
+                    _ => None,
+                };
+
+                output
+                #(.or_else(|| <dyn #supertraits as ::obce::ink_lang::DefaultOutputs>::default_output(hash)))*
+            }
+        }
+
+        #supertraits_impls
+
+        #call_enum
+
+        #substrate_trait_item
+
         #ink_trait_item
+
+        #ink_struct
+    };
+
+    // With associated types, both sides use different types in descriptions,
+    // so the generated code is emitted for each side separately.
+    let side_cfg: Attribute = match side {
+        Side::Any => return Ok(expanded),
+        Side::Ink => parse_quote!(#[cfg(all(feature = "ink", not(feature = "substrate")))]),
+        Side::Substrate => parse_quote!(#[cfg(feature = "substrate")]),
+    };
+
+    let mut file: File = parse2(expanded)?;
+
+    for item in &mut file.items {
+        let attrs = match item {
+            Item::Const(item) => &mut item.attrs,
+            Item::Enum(item) => &mut item.attrs,
+            Item::Impl(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            Item::Trait(item) => &mut item.attrs,
+            _ => {
+                unreachable!("only constants, enums, implementations, structs and traits are generated for definitions")
+            }
+        };
+
+        attrs.insert(0, side_cfg.clone());
+    }
+
+    Ok(quote!(#file))
+}
+
+/// ink! chain extension struct, that implements the definition with `#[obce::ink_lang::extension]`.
+fn ink_struct(trait_item: &ItemTrait, ink_struct: &Ident) -> Result<TokenStream, Error> {
+    let vis = &trait_item.vis;
+    let trait_name = &trait_item.ident;
+
+    let doc = format!("ink! chain extension, that provides [`{trait_name}`] methods.");
+
+    let extension = extension::ink(
+        TokenStream::new(),
+        quote! {
+            #[doc = #doc]
+            #[cfg(feature = "ink")]
+            #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ::scale::Encode, ::scale::Decode)]
+            #[cfg_attr(
+                feature = "std",
+                derive(::scale_info::TypeInfo, ::obce::ink_lang::storage::traits::StorageLayout)
+            )]
+            #vis struct #ink_struct;
+        },
+    )?;
+
+    Ok(quote! {
+        #extension
+
+        #[cfg(all(feature = "ink", not(feature = "substrate")))]
+        impl #trait_name for #ink_struct {}
     })
 }
 
-fn find_id<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Result<Option<u16>, Error> {
+/// Implementations that allow `#[obce::implementation]` and `#[obce::mock]`
+/// to reach methods inherited from supertraits.
+fn supertraits_impls(
+    trait_item: &ItemTrait,
+    trait_attrs: &TraitAttrs,
+    methods: &[Method],
+    supertraits: &[&Path],
+) -> TokenStream {
+    let trait_name = &trait_item.ident;
+    let (_, types, where_clause) = trait_item.generics.split_for_impl();
+
+    let mut owner_generics = trait_item.generics.clone();
+    owner_generics.params.push(parse_quote!(__ObcePath));
+    owner_generics
+        .params
+        .push(parse_quote!(const __OBCE_HASH: ::core::primitive::u32));
+    let (owner_impls, _, _) = owner_generics.split_for_impl();
+
+    let method_owners = supertraits.iter().enumerate().map(|(idx, supertrait)| {
+        let mut owner_where = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+        owner_where.predicates.push(parse_quote! {
+            dyn #supertrait: ::obce::codegen::MethodOwner<__OBCE_HASH, __ObcePath>
+        });
+
+        quote! {
+            impl #owner_impls ::obce::codegen::MethodOwner<__OBCE_HASH, ::obce::codegen::Inherited<__ObcePath, #idx>>
+                for dyn #trait_name #types #owner_where
+            {
+                type Owner = <dyn #supertrait as ::obce::codegen::MethodOwner<__OBCE_HASH, __ObcePath>>::Owner;
+            }
+        }
+    });
+
+    let mut dispatch_generics = trait_item.generics.clone();
+    dispatch_generics.params.push(parse_quote!(__ObceExtension));
+    dispatch_generics.params.push(parse_quote!(__ObceE));
+    dispatch_generics.params.push(parse_quote!(__ObceT));
+    dispatch_generics.params.push(parse_quote!(__ObceEnv));
+    let dispatch_where = dispatch_generics.make_where_clause();
+
+    if !supertraits.is_empty() {
+        dispatch_where.predicates.push(parse_quote! {
+            __ObceEnv: ::obce::substrate::ChainExtensionEnvironment<__ObceE, __ObceT>
+        });
+    }
+
+    for supertrait in supertraits {
+        dispatch_where.predicates.push(parse_quote! {
+            __ObceExtension: ::obce::substrate::Dispatch<dyn #supertrait, __ObceE, __ObceT, __ObceEnv>
+        });
+    }
+
+    let (dispatch_impls, _, dispatch_where) = dispatch_generics.split_for_impl();
+
+    // Supertraits are checked in order, while methods of the chain extension itself
+    // are dispatched before reaching this implementation.
+    let dispatch_supertraits = quote! {
+        #[cfg(feature = "substrate")]
+        impl #dispatch_impls ::obce::substrate::DispatchSupertraits<__ObceExtension, __ObceE, __ObceT, __ObceEnv>
+            for dyn #trait_name #types #dispatch_where
+        {
+            #[allow(unused_variables)]
+            fn handles(ext_id: ::core::primitive::u16, func_id: ::core::primitive::u16) -> bool {
+                false
+                #(|| <__ObceExtension as ::obce::substrate::Dispatch<dyn #supertraits, __ObceE, __ObceT, __ObceEnv>>::handles(
+                    ext_id, func_id
+                ))*
+            }
+
+            #[allow(unused_variables)]
+            fn dispatch(
+                extension: &mut __ObceExtension,
+                env: __ObceEnv,
+            ) -> ::core::result::Result<
+                ::obce::substrate::pallet_contracts::chain_extension::RetVal,
+                ::obce::substrate::CriticalError
+            > {
+                #(
+                    if <__ObceExtension as ::obce::substrate::Dispatch<dyn #supertraits, __ObceE, __ObceT, __ObceEnv>>::handles(
+                        env.ext_id(), env.func_id()
+                    ) {
+                        return <__ObceExtension as ::obce::substrate::Dispatch<dyn #supertraits, __ObceE, __ObceT, __ObceEnv>>::dispatch(
+                            extension, env
+                        )
+                    }
+                )*
+
+                ::core::result::Result::Err(::obce::substrate::CriticalError::Other("InvalidFunctionId"))
+            }
+        }
+    };
+
+    // Methods of supertraits that share the chain extension identifier are dispatched
+    // by the same chain extension, so their identifiers must not clash.
+    //
+    // Supertraits of generic definitions may depend on generic parameters,
+    // which are not available in constants.
+    let id_checks = (trait_item.generics.params.is_empty() && !supertraits.is_empty()).then(|| {
+        let trait_id = trait_attrs.id;
+        let ids = methods
+            .iter()
+            .flat_map(|Method { id, legacy_ids, .. }| iter::once(id).chain(legacy_ids));
+
+        let checks = supertraits.iter().map(|supertrait| {
+            let message = format!(
+                "`{trait_name}` reuses a method identifier of `{}` under the same chain extension identifier",
+                quote!(#supertrait).to_string().split_whitespace().collect::<String>()
+            );
+
+            quote! {
+                assert!(
+                    <dyn #supertrait as ::obce::codegen::ExtensionDescription>::ID != #trait_id
+                        || !::obce::codegen::shares_method_id(
+                            <dyn #supertrait as ::obce::codegen::ExtensionDescription>::METHODS,
+                            IDS,
+                        ),
+                    #message
+                );
+            }
+        });
+
+        quote! {
+            const _: () = {
+                const IDS: &[::core::primitive::u16] = &[#(#ids),*];
+
+                #(#checks)*
+            };
+        }
+    });
+
+    quote! {
+        #(#method_owners)*
+
+        #dispatch_supertraits
+
+        #id_checks
+    }
+}
+
+fn call_enum(
+    trait_item: &ItemTrait,
+    trait_id: u16,
+    call_derive: &[Path],
+    methods: &[Method],
+    side: Side,
+) -> TokenStream {
+    let vis = &trait_item.vis;
+    let trait_name = &trait_item.ident;
+    let enum_name = format_ident!("{}Call", trait_name);
+
+    let enum_doc = format!("Calls of [`{trait_name}`] chain extension methods, holding typed method inputs.");
+
+    let variants: Vec<_> = methods
+        .iter()
+        .map(|method| format_ident!("{}", to_upper_camel_case(&method.name)))
+        .collect();
+
+    let bindings: Vec<Vec<_>> = methods
+        .iter()
+        .map(|method| {
+            (0..method.input_tys.len())
+                .map(|idx| format_ident!("__obce_input_{}", idx))
+                .collect()
+        })
+        .collect();
+
+    let variant_defs = methods.iter().zip(&variants).map(|(method, variant)| {
+        let doc = format!("Call of [`{trait_name}::{}`].", method.name);
+        let input_tys = &method.input_tys;
+
+        quote! {
+            #[doc = #doc]
+            #variant(#(#input_tys),*)
+        }
+    });
+
+    let func_ids = methods.iter().zip(&variants).map(|(method, variant)| {
+        let id_for_call = (trait_id as u32) << 16 | (method.id as u32);
+
+        quote! {
+            Self::#variant(..) => #id_for_call,
+        }
+    });
+
+    let encode_arms = variants.iter().zip(&bindings).map(|(variant, bindings)| {
+        quote! {
+            Self::#variant(#(ref #bindings),*) => ::scale::Encode::encode(&(#(#bindings),*)),
+        }
+    });
+
+    let decode_arms = methods
+        .iter()
+        .zip(&variants)
+        .zip(&bindings)
+        .map(|((method, variant), bindings)| {
+            let id_for_call = (trait_id as u32) << 16 | (method.id as u32);
+            let input_tokens = &method.input_tokens;
+
+            quote! {
+                #id_for_call => {
+                    let (#(#bindings),*) = <#input_tokens as ::scale::Decode>::decode(&mut &input[..])?;
+                    Ok(Self::#variant(#(#bindings),*))
+                }
+            }
+        });
+
+    // ink! trait methods take borrowed arguments, which are borrowed from the owned call inputs.
+    let dispatch_arms = |borrowed: bool| {
+        methods
+            .iter()
+            .zip(&variants)
+            .zip(&bindings)
+            .map(move |((method, variant), bindings)| {
+                let name = &method.name;
+
+                let args = bindings.iter().zip(method.arg_tys.iter().zip(&method.input_tys)).map(
+                    |(binding, (arg_ty, input_ty))| {
+                        if borrowed && arg_ty != input_ty {
+                            quote!(&#binding)
+                        } else {
+                            quote!(#binding)
+                        }
+                    },
+                );
+
+                quote! {
+                    Self::#variant(#(#bindings),*) => ::scale::Encode::encode(
+                        &<E as #trait_name>::#name(extension #(, #args)*)
+                    ),
+                }
+            })
+    };
+
+    let has_borrowed_args = methods.iter().any(|method| method.arg_tys != method.input_tys);
+
+    let dispatch_variants = match side {
+        Side::Any if has_borrowed_args => {
+            vec![
+                (quote!(feature = "substrate"), false),
+                (quote!(all(feature = "ink", not(feature = "substrate"))), true),
+            ]
+        }
+        Side::Any => vec![(quote!(any(feature = "substrate", feature = "ink")), false)],
+        Side::Ink => vec![(quote!(all(feature = "ink", not(feature = "substrate"))), true)],
+        Side::Substrate => vec![(quote!(feature = "substrate"), false)],
+    };
+
+    let dispatch_fns = dispatch_variants.into_iter().map(|(cfg, borrowed)| {
+        let dispatch_arms = dispatch_arms(borrowed);
+
+        quote! {
+            /// Call the corresponding method on `extension`, returning the encoded output.
+            #[cfg(#cfg)]
+            pub fn dispatch<E: ?Sized + #trait_name>(self, extension: &mut E) -> ::scale::alloc::vec::Vec<u8> {
+                match self {
+                    #(#dispatch_arms)*
+                }
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #enum_doc]
+        #[derive(::scale::Encode, ::scale::Decode #(, #call_derive)*)]
+        #vis enum #enum_name {
+            #(#variant_defs,)*
+        }
+
+        #[allow(unused_parens)]
+        impl #enum_name {
+            /// Identifier of the called method, as passed by ink! smart contracts.
+            pub fn func_id(&self) -> ::core::primitive::u32 {
+                match *self {
+                    #(#func_ids)*
+                }
+            }
+
+            /// Raw input of the call, as passed by ink! smart contracts.
+            pub fn encode_input(&self) -> ::scale::alloc::vec::Vec<u8> {
+                match *self {
+                    #(#encode_arms)*
+                }
+            }
+
+            /// Decode a call from the method identifier and raw input passed by ink! smart contracts.
+            pub fn decode_input(func_id: ::core::primitive::u32, input: &[u8]) -> ::core::result::Result<Self, ::scale::Error> {
+                match func_id {
+                    #(#decode_arms)*
+                    _ => Err("unknown chain extension method identifier".into()),
+                }
+            }
+
+            #(#dispatch_fns)*
+        }
+    }
+}
+
+/// Method signature, that is used to derive method identifiers with `id_from_signature` enabled.
+///
+/// Whitespace is removed from type tokens, so that formatting doesn't affect the signature.
+fn signature(method_item: &TraitItemMethod) -> String {
+    let normalize = |tokens: TokenStream| tokens.to_string().split_whitespace().collect::<String>();
+
+    let inputs = method_item
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|input| {
+            if let FnArg::Typed(pat) = input {
+                let ty = &pat.ty;
+                Some(normalize(quote!(#ty)))
+            } else {
+                None
+            }
+        })
+        .join(",");
+
+    let output = if let ReturnType::Type(_, ty) = &method_item.sig.output {
+        normalize(quote!(#ty))
+    } else {
+        "()".to_string()
+    };
+
+    format!("{}({inputs})->{output}", method_item.sig.ident)
+}
+
+fn find_version<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Result<Option<u16>, Error> {
     iter.into_iter()
         .find_map(|arg| {
             match arg {
-                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("id") => {
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("version") => {
                     Some(match &value.lit {
-                        Lit::Int(lit_int) => lit_int.base10_parse::<u16>(),
-                        Lit::Str(lit_str) => Ok(into_u16(lit_str.value())),
-                        _ => Err(format_err_spanned!(value, "id should be integer or string")),
+                        Lit::Int(lit_int) => {
+                            lit_int.base10_parse::<u16>().and_then(|version| {
+                                if version == 0 {
+                                    Err(format_err_spanned!(value, "version should start from 1"))
+                                } else {
+                                    Ok(version)
+                                }
+                            })
+                        }
+                        _ => Err(format_err_spanned!(value, "version should be integer")),
                     })
                 }
                 _ => None,
@@ -253,3 +1330,81 @@ fn find_id<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Result<Option
         })
         .transpose()
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::{
+        parse2,
+        File,
+        Item,
+        Lit,
+        Meta,
+        MetaNameValue,
+        TraitItem,
+    };
+
+    use super::generate;
+
+    fn method_docs(file: &File, method: &str) -> Vec<String> {
+        file.items
+            .iter()
+            .filter_map(|item| if let Item::Trait(item) = item { Some(item) } else { None })
+            // Both ink! and Substrate traits have the same method docs.
+            .find(|item| item.ident == "Trait")
+            .into_iter()
+            .flat_map(|item| &item.items)
+            .filter_map(|item| {
+                if let TraitItem::Method(item) = item {
+                    Some(item)
+                } else {
+                    None
+                }
+            })
+            .filter(|item| item.sig.ident == method)
+            .flat_map(|item| &item.attrs)
+            .filter_map(|attr| {
+                if let Ok(Meta::NameValue(MetaNameValue { lit: Lit::Str(doc), .. })) = attr.parse_meta() {
+                    Some(doc.value())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn computed_ids_are_documented() {
+        let expanded = generate(
+            quote!(id_from_signature),
+            quote! {
+                pub trait Trait {
+                    /// Method with a computed identifier.
+                    fn method(&mut self, val: u32, data: Vec<u8>) -> u64;
+
+                    fn another_method(&self);
+
+                    #[obce(id = 1)]
+                    fn explicit_method(&self, val: u32);
+                }
+            },
+        )
+        .unwrap();
+
+        let file: File = parse2(expanded).unwrap();
+
+        assert_eq!(
+            method_docs(&file, "method"),
+            vec![
+                " Method with a computed identifier.",
+                "",
+                " Chain extension method identifier `0x12f1` is computed from `method(u32,Vec<u8>)->u64` signature.",
+            ]
+        );
+        assert_eq!(
+            method_docs(&file, "another_method"),
+            vec![" Chain extension method identifier `0xe5de` is computed from `another_method()->()` signature."]
+        );
+        assert!(method_docs(&file, "explicit_method").is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use std::collections::HashSet;
+
+use proc_macro2::{
+    Ident,
+    TokenStream,
+};
+use quote::{
+    format_ident,
+    quote,
+};
+use syn::{
+    bracketed,
+    parse::{
+        Parse,
+        ParseStream,
+    },
+    parse2,
+    punctuated::Punctuated,
+    Attribute,
+    Error,
+    Token,
+    Type,
+    Visibility,
+};
+
+use crate::{
+    format_err_spanned,
+    utils::to_snake_case,
+};
+
+struct EnvironmentInput {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    base: Type,
+    extensions: Punctuated<ExtensionInput, Token![,]>,
+}
+
+impl Parse for EnvironmentInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let name = input.parse()?;
+        let _: Token![:] = input.parse()?;
+        let base = input.parse()?;
+        let _: Token![,] = input.parse()?;
+
+        let key: Ident = input.parse()?;
+
+        if key != "extensions" {
+            return Err(format_err_spanned!(key, "expected `extensions = [...]`"))
+        }
+
+        let _: Token![=] = input.parse()?;
+
+        let content;
+        bracketed!(content in input);
+        let extensions = content.parse_terminated(ExtensionInput::parse)?;
+
+        if !input.is_empty() {
+            let _: Token![,] = input.parse()?;
+        }
+
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            base,
+            extensions,
+        })
+    }
+}
+
+/// Chain extension listed in `extensions = [...]`, optionally with an explicit accessor name.
+struct ExtensionInput {
+    accessor: Option<Ident>,
+    ty: Type,
+}
+
+impl Parse for ExtensionInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let accessor = if input.peek(syn::Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let accessor = input.parse()?;
+            let _: Token![:] = input.parse()?;
+            Some(accessor)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            accessor,
+            ty: input.parse()?,
+        })
+    }
+}
+
+impl ExtensionInput {
+    /// Accessor name, derived from the extension type name without the `Extension` suffix
+    /// unless specified explicitly.
+    fn accessor(&self) -> Result<Ident, Error> {
+        if let Some(accessor) = &self.accessor {
+            return Ok(accessor.clone())
+        }
+
+        let Type::Path(type_path) = &self.ty else {
+            return Err(format_err_spanned!(
+                self.ty,
+                "unable to derive accessor name, specify it explicitly with `name: Extension`"
+            ))
+        };
+
+        let ident = &type_path
+            .path
+            .segments
+            .last()
+            .expect("type paths have at least one segment")
+            .ident;
+
+        let ident_str = ident.to_string();
+
+        let name = match ident_str.strip_suffix("Extension") {
+            Some(name) if !name.is_empty() => name,
+            _ => &ident_str,
+        };
+
+        Ok(Ident::new(&to_snake_case(name), ident.span()))
+    }
+}
+
+pub fn ink(input: TokenStream) -> Result<TokenStream, Error> {
+    let EnvironmentInput {
+        attrs,
+        vis,
+        name,
+        base,
+        extensions,
+    } = parse2(input)?;
+
+    if extensions.is_empty() {
+        return Err(format_err_spanned!(
+            name,
+            "at least one chain extension should be provided"
+        ))
+    }
+
+    let mut accessors = HashSet::new();
+
+    let accessor_methods = extensions
+        .iter()
+        .map(|extension| {
+            let accessor = extension.accessor()?;
+
+            if !accessors.insert(accessor.to_string()) {
+                return Err(format_err_spanned!(
+                    extension.ty,
+                    "duplicate accessor `{}`, specify a different one with `name: Extension`",
+                    accessor
+                ))
+            }
+
+            let ty = &extension.ty;
+
+            Ok(quote! {
+                #[inline(always)]
+                pub fn #accessor(&self) -> <#ty as ::obce::ink_lang::ChainExtensionInstance>::Instance {
+                    <#ty as ::obce::ink_lang::ChainExtensionInstance>::instantiate()
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let extensions_name = format_ident!("{}Extensions", name);
+    let extensions_doc = format!(" Chain extensions available in [`{name}`] environment.");
+
+    Ok(quote! {
+        #(#attrs)*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+        #vis enum #name {}
+
+        impl ::obce::ink_lang::env::Environment for #name {
+            const MAX_EVENT_TOPICS: usize = <#base as ::obce::ink_lang::env::Environment>::MAX_EVENT_TOPICS;
+
+            type AccountId = <#base as ::obce::ink_lang::env::Environment>::AccountId;
+            type Balance = <#base as ::obce::ink_lang::env::Environment>::Balance;
+            type Hash = <#base as ::obce::ink_lang::env::Environment>::Hash;
+            type Timestamp = <#base as ::obce::ink_lang::env::Environment>::Timestamp;
+            type BlockNumber = <#base as ::obce::ink_lang::env::Environment>::BlockNumber;
+
+            type ChainExtension = #extensions_name;
+        }
+
+        #[doc = #extensions_doc]
+        #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+        #vis struct #extensions_name;
+
+        impl #extensions_name {
+            #(#accessor_methods)*
+        }
+
+        impl ::obce::ink_lang::ChainExtensionInstance for #extensions_name {
+            type Instance = #extensions_name;
+
+            fn instantiate() -> Self::Instance {
+                #extensions_name
+            }
+        }
+    })
+}
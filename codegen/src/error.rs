@@ -100,10 +100,35 @@ pub fn generate(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, E
 
     let formatted_ret_val = ret_val_variants.iter().map(|RetValInfo { variant_name, ret_val }| {
         quote! {
-            #ident::#variant_name => Ok(Self::Converging(#ret_val)),
+            #ident::#variant_name => Ok(#ret_val),
         }
     });
 
+    let ret_val_table = ret_val_variants.iter().map(|RetValInfo { variant_name, ret_val }| {
+        let variant_name = variant_name.to_string();
+
+        quote! {
+            ::obce::codegen::RetValInfo {
+                variant: #variant_name,
+                ret_val: #ret_val,
+            }
+        }
+    });
+
+    let error_description_impl = quote! {
+        impl #impl_generics ::obce::codegen::ErrorDescription for #ident #ty_generics #where_clause {
+            const RET_VALS: &'static [::obce::codegen::RetValInfo] = &[#(#ret_val_table),*];
+
+            fn try_into_ret_val(self) -> Result<u32, Self> {
+                #[allow(unreachable_patterns)]
+                match self {
+                    #(#formatted_ret_val)*
+                    _ => Err(self)
+                }
+            }
+        }
+    };
+
     let ret_val_impl = quote! {
         impl #impl_generics ::core::convert::TryFrom<#ident #ty_generics>
             for ::obce::substrate::pallet_contracts::chain_extension::RetVal
@@ -112,10 +137,7 @@ pub fn generate(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, E
             type Error = #ident #ty_generics;
 
             fn try_from(value: #ident #ty_generics) -> Result<Self, #ident #ty_generics> {
-                match value {
-                    #(#formatted_ret_val)*
-                    _ => Err(value)
-                }
+                ::obce::codegen::ErrorDescription::try_into_ret_val(value).map(Self::Converging)
             }
         }
     };
@@ -127,6 +149,8 @@ pub fn generate(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, E
 
         #critical_variant
 
+        #error_description_impl
+
         #[cfg(feature = "substrate")]
         #ret_val_impl
     })
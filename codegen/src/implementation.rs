@@ -21,7 +21,9 @@
 
 use crate::{
     format_err_spanned,
+    types::AttributeArgs,
     utils::{
+        find_id,
         into_u32,
         AttributeParser,
         InputBindings,
@@ -47,9 +49,11 @@ use syn::{
     punctuated::Punctuated,
     Error,
     Expr,
+    FnArg,
     GenericArgument,
     Generics,
     ImplItem,
+    ImplItemMethod,
     ItemImpl,
     Lit,
     Meta,
@@ -61,38 +65,143 @@ use syn::{
 };
 use tuple::Map;
 
-pub fn generate(_attrs: TokenStream, input: TokenStream) -> Result<TokenStream, Error> {
+pub fn generate(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, Error> {
     let impl_item: ItemImpl = parse2(input).unwrap();
 
+    // Implementations of supertraits are dispatched by the implementation of the
+    // chain extension that inherits them, which is the one registered in the runtime.
+    let supertrait = parse2::<AttributeArgs>(attrs)?
+        .iter()
+        .any(|arg| matches!(arg, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("supertrait")));
+
     let mut original_implementation = impl_item.clone();
+    let mut adapters = Vec::new();
+    let mut legacy_ids = Vec::new();
+
+    let mut items = Vec::with_capacity(original_implementation.items.len());
+
+    for item in original_implementation.items {
+        if let ImplItem::Method(mut method_item) = item {
+            let (obce_attrs, other_attrs) = method_item.attrs.iter().cloned().split_attrs()?;
+
+            method_item.attrs = other_attrs;
 
-    let method_items = original_implementation.items.iter_mut().filter_map(|item| {
-        if let ImplItem::Method(method_item) = item {
-            Some(method_item)
+            if let Some(legacy_id) = find_legacy_id(obce_attrs.iter())? {
+                legacy_ids.push((method_item.sig.ident.clone(), legacy_id));
+                adapters.push(method_item);
+            } else {
+                items.push(ImplItem::Method(method_item));
+            }
         } else {
-            None
+            items.push(item);
         }
-    });
+    }
+
+    original_implementation.items = items;
 
-    for method_item in method_items {
-        let (_, other_attrs) = method_item.attrs.iter().cloned().split_attrs()?;
+    let chain_extension = chain_extension_trait_impl(impl_item.clone(), supertrait)?;
 
-        method_item.attrs = other_attrs;
+    if adapters.is_empty() {
+        return Ok(quote! {
+            // Implementation of the trait for `ExtensionContext` with main logic.
+            #original_implementation
+
+            // Implementation of `ChainExtension` from `contract-pallet`
+            #chain_extension
+        })
     }
 
-    let chain_extension = chain_extension_trait_impl(impl_item)?;
+    let legacy_adapters = legacy_adapters_impl(&impl_item, &adapters);
+    let legacy_id_checks = legacy_id_checks(&impl_item, &legacy_ids);
 
     Ok(quote! {
         // Implementation of the trait for `ExtensionContext` with main logic.
         #original_implementation
 
-        // Implementation of `ChainExtension` from `contract-pallet`
-        #chain_extension
+        const _: () = {
+            // Adapters for legacy method identifiers, which are available only to the dispatcher.
+            #legacy_adapters
+
+            #legacy_id_checks
+
+            // Implementation of `ChainExtension` from `contract-pallet`
+            #chain_extension
+        };
+    })
+}
+
+fn legacy_adapters_impl(impl_item: &ItemImpl, adapters: &[ImplItemMethod]) -> TokenStream {
+    let generics = &impl_item.generics;
+    let (impls, types, where_clause) = generics.split_for_impl();
+    let self_ty = &impl_item.self_ty;
+
+    let signatures = adapters.iter().map(|adapter| {
+        let mut sig = adapter.sig.clone();
+
+        // Patterns are not allowed in trait methods without a body.
+        sig.inputs.iter_mut().for_each(|input| {
+            if let FnArg::Typed(pat) = input {
+                *pat.pat = parse_quote!(_);
+            }
+        });
+
+        quote! {
+            #sig;
+        }
+    });
+
+    quote! {
+        trait __ObceLegacyAdapters #generics #where_clause {
+            #(#signatures)*
+        }
+
+        impl #impls __ObceLegacyAdapters #types for #self_ty #where_clause {
+            #(#adapters)*
+        }
+    }
+}
+
+/// Adapters can only serve identifiers listed in `legacy_ids` of the definition,
+/// since other identifiers are either unused by contracts or dispatched to regular methods.
+///
+/// Generic definitions may depend on generic parameters, which are not available in constants.
+fn legacy_id_checks(impl_item: &ItemImpl, legacy_ids: &[(Ident, u16)]) -> Option<TokenStream> {
+    let (_, path, _) = impl_item.trait_.as_ref()?;
+
+    if path
+        .segments
+        .iter()
+        .any(|segment| !matches!(segment.arguments, PathArguments::None))
+    {
+        return None
+    }
+
+    let trait_name = path.segments.last()?.ident.to_string();
+
+    let checks = legacy_ids.iter().map(|(adapter, legacy_id)| {
+        let message = format!(
+            "`{adapter}` adapter serves identifier {legacy_id}, which is not listed in `legacy_ids` of any \
+             `{trait_name}` method"
+        );
+
+        quote! {
+            assert!(
+                ::obce::codegen::is_legacy_id(
+                    <dyn #path as ::obce::codegen::ExtensionDescription>::METHODS,
+                    #legacy_id,
+                ),
+                #message
+            );
+        }
+    });
+
+    Some(quote! {
+        #(#checks)*
     })
 }
 
 #[allow(non_snake_case)]
-fn chain_extension_trait_impl(mut impl_item: ItemImpl) -> Result<TokenStream, Error> {
+fn chain_extension_trait_impl(mut impl_item: ItemImpl, supertrait: bool) -> Result<TokenStream, Error> {
     let context = ExtensionContext::try_from(&impl_item)?;
 
     let namespace = quote! { ::obce::substrate::pallet_contracts::chain_extension:: };
@@ -102,14 +211,36 @@ fn chain_extension_trait_impl(mut impl_item: ItemImpl) -> Result<TokenStream, Er
     let Env = context.obce_env;
     let extension = context.extension;
 
+    let trait_;
+    let dyn_trait;
+    if let Some((_, path, _)) = &impl_item.trait_ {
+        trait_ = path.clone();
+        dyn_trait = quote! { dyn #path };
+    } else {
+        return Err(format_err_spanned!(impl_item, "expected impl trait block",))
+    }
+
     let mut callable_generics = impl_item.generics.clone();
     callable_generics = filter_generics(callable_generics, &context.lifetime1);
+
+    // Methods that are not defined by the chain extension itself are dispatched to supertraits.
+    callable_generics.make_where_clause().predicates.push(parse_quote! {
+        #dyn_trait: ::obce::substrate::DispatchSupertraits<#extension, #E, #T, #Env>
+    });
+
     let (callable_impls, _, callable_where) = callable_generics.split_for_impl();
 
     let mut main_generics = impl_item.generics.clone();
     main_generics = filter_generics(main_generics, &context.lifetime1);
     main_generics = filter_generics(main_generics, &E);
     main_generics = filter_generics(main_generics, &Env);
+
+    // Same as with `Ext` below, runtime configuration is only required by "production" code,
+    // so that implementations can be tested with any `T`.
+    main_generics.make_where_clause().predicates.push(parse_quote! {
+        #T: ::obce::substrate::pallet_contracts::Config
+    });
+
     let (main_impls, _, main_where) = main_generics.split_for_impl();
 
     let mut call_generics = impl_item.generics.clone();
@@ -130,14 +261,12 @@ fn chain_extension_trait_impl(mut impl_item: ItemImpl) -> Result<TokenStream, Er
 
     let (_, _, call_where) = call_generics.split_for_impl();
 
-    let trait_;
-    let dyn_trait;
-    if let Some((_, path, _)) = impl_item.trait_ {
-        trait_ = path.clone();
-        dyn_trait = quote! { dyn #path };
-    } else {
-        return Err(format_err_spanned!(impl_item, "expected impl trait block",))
-    }
+    let mut any_env_generics = call_generics.clone();
+    any_env_generics.make_where_clause().predicates.push(parse_quote! {
+        #Env: ::obce::substrate::ChainExtensionEnvironment<#E, #T>
+    });
+
+    let (_, _, any_env_where) = any_env_generics.split_for_impl();
 
     let methods: Vec<_> = impl_item
         .items
@@ -154,6 +283,7 @@ fn chain_extension_trait_impl(mut impl_item: ItemImpl) -> Result<TokenStream, Er
 
             method.attrs = other_attrs;
 
+            let legacy_id = find_legacy_id(obce_attrs.iter())?;
             let hash = into_u32(&method.sig.ident);
             let method_name = &method.sig.ident;
 
@@ -186,44 +316,133 @@ fn chain_extension_trait_impl(mut impl_item: ItemImpl) -> Result<TokenStream, Er
                 )
             };
 
-            Result::<_, Error>::Ok(quote! {
-                <#dyn_trait as ::obce::codegen::MethodDescription<#hash>>::ID => {
-                    #read_with_charge
-                    let mut context = ::obce::substrate::ExtensionContext::new(self, env, #pre_charge_arg);
-                    #[allow(clippy::unnecessary_mut_passed)]
-                    let result = <_ as #trait_>::#method_name(
+            let call = if legacy_id.is_some() {
+                quote! {
+                    context.#method_name(#(#call_params),*)
+                }
+            } else {
+                quote! {
+                    <_ as #trait_>::#method_name(
                         &mut context
                         #(, #call_params)*
-                    );
+                    )
+                }
+            };
 
-                    // If result is `Result` and `Err` is critical, return from the `call`.
-                    // Otherwise, try to convert result to RetVal, and return it or encode the result into the buffer.
-                    let result = ::obce::to_critical_error!(result)?;
-                    #ret_val_tokens
-                    <_ as ::scale::Encode>::using_encoded(&result, |w| context.env.write(w, true, None))?;
+            let body = quote! {{
+                #read_with_charge
+                let mut context = ::obce::substrate::ExtensionContext::new(self, env, #pre_charge_arg);
+                #[allow(clippy::unnecessary_mut_passed)]
+                let result = #call;
+
+                // If result is `Result` and `Err` is critical, return from the `call`.
+                // Otherwise, try to convert result to RetVal, and return it or encode the result into the buffer.
+                let result = ::obce::to_critical_error!(result)?;
+                #ret_val_tokens
+                <_ as ::scale::Encode>::using_encoded(&result, |w| context.env.write(w, true, None))?;
+
+                Ok(#namespace RetVal::Converging(0))
+            }};
+
+            Result::<_, Error>::Ok(
+                if let Some(legacy_id) = legacy_id {
+                    (
+                        quote! {
+                            #legacy_id => #body,
+                        },
+                        quote! {
+                            #legacy_id => true,
+                        },
+                        None,
+                    )
+                } else {
+                    (
+                        quote! {
+                            <#dyn_trait as ::obce::codegen::MethodDescription<#hash>>::ID => #body,
+                        },
+                        quote! {
+                            <#dyn_trait as ::obce::codegen::MethodDescription<#hash>>::ID => true,
+                        },
+                        // Legacy identifiers without an adapter are dispatched to the method itself.
+                        Some((
+                            quote! {
+                                func_id if <#dyn_trait as ::obce::codegen::MethodDescription<#hash>>::LEGACY_IDS
+                                    .contains(&func_id) => #body,
+                            },
+                            quote! {
+                                func_id if <#dyn_trait as ::obce::codegen::MethodDescription<#hash>>::LEGACY_IDS
+                                    .contains(&func_id) => true,
+                            },
+                        )),
+                    )
                 },
-            })
+            )
         })
-        .try_collect()?;
+        .collect::<Result<Vec<_>, Error>>()?;
 
-    Ok(quote! {
-        impl #callable_impls ::obce::substrate::CallableChainExtension<#E, #T, #Env> for #extension
+    let (methods, handled, legacy_methods): (Vec<_>, Vec<_>, Vec<_>) = methods.into_iter().multiunzip();
+    let (legacy_methods, legacy_handled): (Vec<_>, Vec<_>) = legacy_methods.into_iter().flatten().unzip();
+
+    let dispatch = quote! {
+        impl #callable_impls ::obce::substrate::Dispatch<#dyn_trait, #E, #T, #Env> for #extension
             #callable_where
         {
-            fn call(&mut self, mut env: #Env) -> ::core::result::Result<
+            fn handles(ext_id: ::core::primitive::u16, func_id: ::core::primitive::u16) -> bool {
+                let defined = ext_id == <#dyn_trait as ::obce::codegen::ExtensionDescription>::ID
+                    && match func_id {
+                        #(#handled)*
+                        #(#legacy_handled)*
+                        _ => false,
+                    };
+
+                defined || <#dyn_trait as ::obce::substrate::DispatchSupertraits<#extension, #E, #T, #Env>>::handles(
+                    ext_id, func_id
+                )
+            }
+
+            fn dispatch(&mut self, mut env: #Env) -> ::core::result::Result<
                 #namespace RetVal,
                 ::obce::substrate::CriticalError
             > {
+                // Supertraits with their own chain extension identifiers may reuse method identifiers.
+                if env.ext_id() != <#dyn_trait as ::obce::codegen::ExtensionDescription>::ID
+                    && <#dyn_trait as ::obce::substrate::DispatchSupertraits<#extension, #E, #T, #Env>>::handles(
+                        env.ext_id(), env.func_id()
+                    )
+                {
+                    return <#dyn_trait as ::obce::substrate::DispatchSupertraits<#extension, #E, #T, #Env>>::dispatch(
+                        self, env
+                    )
+                }
+
                 let len = env.in_len();
 
                 match env.func_id() {
                     #(#methods)*
-                    _ => ::core::result::Result::Err(::obce::substrate::CriticalError::Other(
-                        "InvalidFunctionId"
-                    ))?,
-                };
+                    #(#legacy_methods)*
+                    _ => <#dyn_trait as ::obce::substrate::DispatchSupertraits<#extension, #E, #T, #Env>>::dispatch(
+                        self, env
+                    ),
+                }
+            }
+        }
+    };
 
-                Ok(#namespace RetVal::Converging(0))
+    if supertrait {
+        return Ok(dispatch)
+    }
+
+    Ok(quote! {
+        #dispatch
+
+        impl #callable_impls ::obce::substrate::CallableChainExtension<#E, #T, #Env> for #extension
+            #callable_where
+        {
+            fn call(&mut self, env: #Env) -> ::core::result::Result<
+                #namespace RetVal,
+                ::obce::substrate::CriticalError
+            > {
+                <#extension as ::obce::substrate::Dispatch<#dyn_trait, #E, #T, #Env>>::dispatch(self, env)
             }
         }
 
@@ -241,6 +460,17 @@ fn chain_extension_trait_impl(mut impl_item: ItemImpl) -> Result<TokenStream, Er
         impl #main_impls #namespace RegisteredChainExtension<#T> for #extension #main_where {
             const ID: ::core::primitive::u16 = <#dyn_trait as ::obce::codegen::ExtensionDescription>::ID;
         }
+
+        impl #main_impls ::obce::substrate::GenericChainExtension<#T> for #extension #main_where {
+            fn call_generic<#E, #Env>(&mut self, env: #Env) -> ::core::result::Result<
+                #namespace RetVal,
+                ::obce::substrate::CriticalError
+            >
+                #any_env_where
+            {
+                <#extension as ::obce::substrate::CallableChainExtension<#E, #T, #Env>>::call(self, env)
+            }
+        }
     })
 }
 
@@ -352,6 +582,28 @@ fn is_subsequence<T: PartialEq + core::fmt::Debug>(src: &[T], search: &[T]) -> b
     false
 }
 
+fn find_legacy_id<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Result<Option<u16>, Error> {
+    let legacy = iter.into_iter().find_map(|attr| {
+        let NestedMeta::Meta(Meta::List(list)) = attr else {
+            return None;
+        };
+
+        list.path.is_ident("legacy").then_some(list)
+    });
+
+    let Some(legacy) = legacy else { return Ok(None) };
+
+    match find_id(legacy.nested.iter())? {
+        Some(id) => Ok(Some(id)),
+        None => {
+            Err(format_err_spanned!(
+                legacy,
+                "`legacy` attribute should contain a legacy method identifier as `legacy(id = ...)`"
+            ))
+        }
+    }
+}
+
 fn handle_ret_val_attribute<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Option<TokenStream> {
     let should_handle = iter.into_iter().any(|attr| {
         if let NestedMeta::Meta(Meta::Path(path)) = attr {
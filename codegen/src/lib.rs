@@ -20,6 +20,7 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 pub mod definition;
+pub mod environment;
 pub mod error;
 pub mod extension;
 pub mod hash;
@@ -1,3 +1,6 @@
+use std::iter;
+
+use darling::FromMeta;
 use proc_macro2::TokenStream;
 use quote::{
     format_ident,
@@ -6,24 +9,134 @@ use quote::{
 use syn::{
     parse2,
     parse_quote,
+    parse_str,
+    punctuated::Punctuated,
     Error,
+    Expr,
+    FnArg,
     ImplItem,
+    ImplItemMethod,
+    Item,
     ItemImpl,
     ItemTrait,
+    Lit,
+    Meta,
+    NestedMeta,
+    Path,
+    ReturnType,
+    Stmt,
+    Token,
     TraitItem,
     TraitItemMethod,
+    Type,
+    WherePredicate,
 };
 
 use crate::{
     format_err_spanned,
+    types::AttributeArgs,
     utils::{
         into_u32,
+        to_snake_case,
+        AttributeParser,
         InputBindings,
+        LitOrPath,
+        MetaUtils,
     },
 };
 
-pub fn generate(_: TokenStream, input: TokenStream) -> Result<TokenStream, Error> {
-    let mut impl_item: ItemImpl = parse2(input)?;
+fn default_register_fn() -> bool {
+    true
+}
+
+#[derive(FromMeta)]
+struct MockAttrs {
+    #[darling(default = "default_register_fn")]
+    register_fn: bool,
+}
+
+struct MockMethod {
+    item: ImplItemMethod,
+    has_body: bool,
+    ret_val: bool,
+    weight: Option<Expr>,
+}
+
+/// Check if a method was declared without a body.
+///
+/// `syn` accepts such methods inside of `impl` blocks, representing the missing body
+/// as a block with a single verbatim `;` item.
+fn is_bodyless(method: &ImplItemMethod) -> bool {
+    matches!(
+        &method.block.stmts[..],
+        [Stmt::Item(Item::Verbatim(tokens))] if tokens.to_string() == ";"
+    )
+}
+
+/// Parse `#[obce(weight(expr = ...))]` attribute, same as the one used in implementation.
+///
+/// Since mocks have no access to pallets, `dispatch` weight is not supported.
+fn handle_weight_attribute<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Result<Option<Expr>, Error> {
+    let weight_params = iter.into_iter().find_map(|attr| {
+        let NestedMeta::Meta(Meta::List(list)) = attr else {
+            return None;
+        };
+
+        let ident = list.path.get_ident()?;
+
+        (ident == "weight").then_some((&list.nested, ident))
+    });
+
+    let Some((weight_params, weight_ident)) = weight_params else {
+        return Ok(None);
+    };
+
+    if let Some((_, ident)) = weight_params.iter().find_by_name("dispatch") {
+        return Err(format_err_spanned!(
+            ident,
+            "`dispatch` weight is not supported in mocks, use `expr` instead"
+        ))
+    }
+
+    match weight_params.iter().find_by_name("expr") {
+        Some((LitOrPath::Lit(Lit::Str(expr)), _)) => Ok(Some(parse_str(&expr.value())?)),
+        Some((_, ident)) => {
+            Err(format_err_spanned!(
+                ident,
+                "`expr` attribute should contain an expression that returns `Weight`"
+            ))
+        }
+        None => Err(format_err_spanned!(weight_ident, r#""expr" attribute is expected"#)),
+    }
+}
+
+/// Bounds that resolve the definition providing the mocked method via `__ObcePath`.
+///
+/// Method input is bound to the types from the mocked method signature,
+/// since the definition that provides the method is not known to generic code.
+fn owner_predicates(trait_name: &Path, method: &ImplItemMethod) -> Punctuated<WherePredicate, Token![,]> {
+    let hash = into_u32(&method.sig.ident);
+
+    let input_tys = method.sig.inputs.iter().filter_map(|input| {
+        if let FnArg::Typed(pat) = input {
+            Some(&pat.ty)
+        } else {
+            None
+        }
+    });
+
+    parse_quote! {
+        dyn #trait_name: ::obce::codegen::MethodOwner<#hash, __ObcePath>,
+        <dyn #trait_name as ::obce::codegen::MethodOwner<#hash, __ObcePath>>::Owner:
+            ::obce::codegen::MethodDescription<#hash, Input = (#(#input_tys),*)>,
+        <<dyn #trait_name as ::obce::codegen::MethodOwner<#hash, __ObcePath>>::Owner
+            as ::obce::codegen::MethodDescription<#hash>>::Output: 'static
+    }
+}
+
+pub fn generate(attrs: TokenStream, input: TokenStream) -> Result<TokenStream, Error> {
+    let impl_item: ItemImpl = parse2(input)?;
+    let mock_attrs = MockAttrs::from_list(&parse2::<AttributeArgs>(attrs)?)?;
 
     let Some((_, trait_name, _)) = impl_item.trait_ else {
         return Err(format_err_spanned!(
@@ -36,9 +149,11 @@ pub fn generate(_: TokenStream, input: TokenStream) -> Result<TokenStream, Error
     let (impls, types, where_clause) = impl_item.generics.split_for_impl();
 
     // We assume that every single item is a method.
+    //
+    // Methods without a body are driven by expectations only.
     let methods = impl_item
         .items
-        .iter_mut()
+        .iter()
         .filter_map(|item| {
             if let ImplItem::Method(method_item) = item {
                 Some(method_item)
@@ -46,7 +161,24 @@ pub fn generate(_: TokenStream, input: TokenStream) -> Result<TokenStream, Error
                 None
             }
         })
-        .collect::<Vec<_>>();
+        .map(|method_item| {
+            let (obce_attrs, other_attrs) = method_item.attrs.iter().cloned().split_attrs()?;
+
+            let ret_val = matches!(obce_attrs.iter().find_by_name("ret_val"), Some((LitOrPath::Path, _)));
+
+            let weight = handle_weight_attribute(obce_attrs.iter())?;
+
+            Ok(MockMethod {
+                item: ImplItemMethod {
+                    attrs: other_attrs,
+                    ..method_item.clone()
+                },
+                has_body: !is_bodyless(method_item),
+                ret_val,
+                weight,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
 
     let mut mock_trait: ItemTrait = parse_quote! {
         trait MockTrait {}
@@ -55,7 +187,8 @@ pub fn generate(_: TokenStream, input: TokenStream) -> Result<TokenStream, Error
     mock_trait.generics = impl_item.generics.clone();
     mock_trait.items = methods
         .iter()
-        .map(|method| (**method).clone())
+        .filter(|method| method.has_body)
+        .map(|method| method.item.clone())
         .map(|val| {
             TraitItem::Method(TraitItemMethod {
                 attrs: val.attrs,
@@ -73,66 +206,159 @@ pub fn generate(_: TokenStream, input: TokenStream) -> Result<TokenStream, Error
     mock_impl.generics = impl_item.generics.clone();
     mock_impl.items = methods
         .iter()
-        .map(|method| (**method).clone())
+        .filter(|method| method.has_body)
+        .map(|method| method.item.clone())
         .map(ImplItem::Method)
         .collect();
 
     let proxies = methods.iter()
-        .map(|method| {
+        .map(|MockMethod { item: method, has_body, ret_val, weight }| {
             let hash = into_u32(&method.sig.ident);
 
             let method_name = &method.sig.ident;
             let proxy_name = format_ident!("ProxyFor{}", hash);
-            let proxy_where_clause = if let Some(mut where_clause) = where_clause.cloned() {
-                where_clause.predicates.push(parse_quote! {
-                    dyn #trait_name: ::obce::codegen::ExtensionDescription,
-                });
-                where_clause.predicates.push(parse_quote! {
-                    <dyn #trait_name as ::obce::codegen::MethodDescription<#hash>>::Output: ::scale::Encode,
+
+            // Methods can be inherited from supertraits, in which case the definition
+            // that provides the method is inferred via `__ObcePath`.
+            let owner = quote! {
+                <dyn #trait_name as ::obce::codegen::MethodOwner<#hash, __ObcePath>>::Owner
+            };
+            let description = quote! {
+                <#owner as ::obce::codegen::MethodDescription<#hash>>
+            };
+
+            let mut proxy_generics = impl_item.generics.clone();
+            proxy_generics.params.push(parse_quote!(__ObcePath));
+            let proxy_where_clause = proxy_generics.make_where_clause();
+            proxy_where_clause.predicates.extend(owner_predicates(&trait_name, method));
+            proxy_where_clause.predicates.push(parse_quote! {
+                #description::Output: ::scale::Encode
+            });
+
+            if *ret_val {
+                proxy_where_clause.predicates.push(parse_quote! {
+                    #description::Output: ::obce::ink_lang::mock::TryIntoRetVal
                 });
-                where_clause.predicates.push(parse_quote! {
-                    <dyn #trait_name as ::obce::codegen::MethodDescription<#hash>>::Input: ::scale::Decode
+            }
+
+            // Mocked methods may return `Result<_, Trap>` instead of the method output.
+            if *has_body {
+                let mock_output: Type = if let ReturnType::Type(_, ty) = &method.sig.output {
+                    (**ty).clone()
+                } else {
+                    parse_quote!(())
+                };
+
+                proxy_where_clause.predicates.push(parse_quote! {
+                    #mock_output: ::obce::ink_lang::mock::IntoMockOutput<#description::Output>
                 });
-                where_clause
-            } else {
-                parse_quote! {
-                    where
-                        dyn #trait_name: ::obce::codegen::ExtensionDescription,
-                        <dyn #trait_name as ::obce::codegen::MethodDescription<#hash>>::Output: ::scale::Encode,
-                        <dyn #trait_name as ::obce::codegen::MethodDescription<#hash>>::Input: ::scale::Decode
-                }
-            };
+            }
+
+            let (proxy_impls, proxy_types, proxy_where_clause) = proxy_generics.split_for_impl();
 
             let input_bindings = InputBindings::from_iter(&method.sig.inputs);
             let lhs_pat = input_bindings.lhs_pat(Some(parse_quote! {
-                <dyn #trait_name as ::obce::codegen::MethodDescription<#hash>>::Input
+                #description::Input
             }));
             let call_params = input_bindings.iter_call_params();
 
+            // Methods marked with `#[obce(ret_val)]` return a status code instead of
+            // writing errors to the output buffer, same as the implementation does.
+            let ret_val_tokens = ret_val.then(|| {
+                quote! {
+                    let call_output = match ::obce::ink_lang::mock::TryIntoRetVal::try_into_ret_val(call_output) {
+                        ::core::result::Result::Ok(status) => return status,
+                        ::core::result::Result::Err(call_output) => call_output,
+                    };
+                }
+            });
+
+            // Weight is charged before the call, the same way the implementation does it.
+            let charge_weight = weight.as_ref().map(|weight| {
+                let raw_map = input_bindings.raw_special_mapping();
+
+                quote! {
+                    let weight = {
+                        let #lhs_pat = ::scale::Decode::decode(&mut &bytes[..])
+                            .unwrap();
+
+                        #[allow(unused_variables)]
+                        #raw_map
+                        #weight
+                    };
+
+                    if let Err(trap) = self.0.borrow_mut().charge_weight(weight) {
+                        ::std::panic::panic_any(trap);
+                    }
+                }
+            });
+
+            // Methods without a body can only be called through expectations.
+            let fallback = if *has_body {
+                quote! {
+                    let #lhs_pat = input;
+
+                    #[allow(clippy::unnecessary_mut_passed)]
+                    let mock_output = <#item as MockTrait #types>::#method_name(
+                        self.0.borrow_mut().context()
+                        #(, #call_params)*
+                    );
+
+                    ::obce::ink_lang::mock::IntoMockOutput::into_mock_output(mock_output)
+                }
+            } else {
+                quote! {
+                    let _ = input;
+
+                    panic!(
+                        "unexpected call to `{}`: no matching expectation provides a return value",
+                        stringify!(#method_name)
+                    )
+                }
+            };
+
             quote! {
-                struct #proxy_name #types (::std::rc::Rc<::std::cell::RefCell<#item>>);
+                struct #proxy_name #proxy_types (
+                    ::std::rc::Rc<::std::cell::RefCell<::obce::ink_lang::mock::MockState<#item>>>,
+                    ::core::marker::PhantomData<fn() -> __ObcePath>,
+                );
 
-                impl #impls ::obce::ink_lang::env::test::ChainExtension for #proxy_name #types #proxy_where_clause {
+                impl #proxy_impls ::obce::ink_lang::env::test::ChainExtension for #proxy_name #proxy_types #proxy_where_clause {
                     fn func_id(&self) -> u32 {
-                        let trait_id = <dyn #trait_name as ::obce::codegen::ExtensionDescription>::ID;
-                        let func_id = <dyn #trait_name as ::obce::codegen::MethodDescription<#hash>>::ID;
+                        let trait_id = <#owner as ::obce::codegen::ExtensionDescription>::ID;
+                        let func_id = #description::ID;
                         (trait_id as u32) << 16 | (func_id as u32)
                     }
 
                     fn call(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
-                        let context = &mut *self.0.borrow_mut();
-
                         let bytes: Vec<u8> = ::scale::Decode::decode(&mut &input[..])
                             .unwrap();
 
-                        let #lhs_pat = ::scale::Decode::decode(&mut &bytes[..])
+                        self.0.borrow_mut().record(
+                            ::obce::ink_lang::env::test::ChainExtension::func_id(self),
+                            bytes.clone(),
+                        );
+
+                        #charge_weight
+
+                        let input: #description::Input = ::scale::Decode::decode(&mut &bytes[..])
                             .unwrap();
 
-                        #[allow(clippy::unnecessary_mut_passed)]
-                        let call_output: <dyn #trait_name as ::obce::codegen::MethodDescription<#hash>>::Output = <#item as MockTrait #types>::#method_name(
-                            context
-                            #(, #call_params)*
-                        );
+                        let expectation = self.0.borrow_mut().expectation(#hash, &input);
+
+                        let expectation_output = match expectation {
+                            Some(expectation) => expectation.call(input),
+                            None => Err(input),
+                        };
+
+                        let call_output: #description::Output = match expectation_output {
+                            Ok(call_output) => call_output,
+                            Err(input) => {
+                                #fallback
+                            }
+                        };
+
+                        #ret_val_tokens
 
                         ::scale::Encode::encode_to(&call_output, output);
 
@@ -140,20 +366,179 @@ pub fn generate(_: TokenStream, input: TokenStream) -> Result<TokenStream, Error
                     }
                 }
 
-                ::obce::ink_lang::env::test::register_chain_extension(#proxy_name(wrapped_context.clone()));
+                ::obce::ink_lang::env::test::register_chain_extension(
+                    #proxy_name(wrapped_context.clone(), ::core::marker::PhantomData),
+                );
             }
         });
 
-    Ok(quote! {
-        pub fn register_chain_extensions #types (ctx: #item) {
-            #[allow(unused_variables)]
-            let wrapped_context = ::std::rc::Rc::new(::std::cell::RefCell::new(ctx));
+    let trait_ident = &trait_name
+        .segments
+        .last()
+        .expect("trait path always has at least one segment")
+        .ident;
+
+    // Generated names are derived from the mock type and the trait, so that several mocks
+    // can be declared in the same module. Unit mocks keep the trait name only.
+    let mock_ident = match &*item {
+        Type::Path(type_path) => type_path.path.segments.last().map(|segment| &segment.ident),
+        _ => None,
+    };
+
+    let expectations_name = match mock_ident {
+        Some(mock_ident) => format_ident!("{}{}Expectations", mock_ident, trait_ident),
+        None => format_ident!("{}Expectations", trait_ident),
+    };
+
+    let (expectations_signatures, expectations_impls): (Vec<_>, Vec<_>) = methods
+        .iter()
+        .map(|MockMethod { item: method, .. }| {
+            let hash = into_u32(&method.sig.ident);
+            let method_name = &method.sig.ident;
+            let expect_name = format_ident!("expect_{}", method_name);
+            let calls_name = format_ident!("{}_calls", method_name);
+
+            let expect_doc = format!("Expect a call to `{method_name}`.");
+            let calls_doc = format!("Decoded arguments of all `{method_name}` calls received so far.");
+
+            let description = quote! {
+                <<dyn #trait_name as ::obce::codegen::MethodOwner<#hash, __ObcePath>>::Owner
+                    as ::obce::codegen::MethodDescription<#hash>>
+            };
+
+            let predicates = owner_predicates(&trait_name, method);
+
+            // `__ObcePath` is inferred at the call site, see `MethodOwner`.
+            let signatures = quote! {
+                #[doc = #expect_doc]
+                fn #expect_name<__ObcePath>(&self) -> ::obce::ink_lang::mock::Expectation<
+                    #description::Input,
+                    #description::Output,
+                >
+                where
+                    #predicates;
+
+                #[doc = #calls_doc]
+                fn #calls_name<__ObcePath>(&self) -> Vec<#description::Input>
+                where
+                    #predicates;
+            };
+
+            let impls = quote! {
+                fn #expect_name<__ObcePath>(&self) -> ::obce::ink_lang::mock::Expectation<
+                    #description::Input,
+                    #description::Output,
+                >
+                where
+                    #predicates,
+                {
+                    self.expectation::<#hash, __ObcePath>(stringify!(#method_name))
+                }
+
+                fn #calls_name<__ObcePath>(&self) -> Vec<#description::Input>
+                where
+                    #predicates,
+                {
+                    self.recorded_inputs::<#hash, __ObcePath>()
+                }
+            };
+
+            (signatures, impls)
+        })
+        .unzip();
 
-            #mock_trait
+    let hashes = methods.iter().map(|MockMethod { item, .. }| into_u32(&item.sig.ident));
 
-            #mock_impl
+    let register_body = quote! {
+        let wrapped_context = ::std::rc::Rc::new(::std::cell::RefCell::new(
+            ::obce::ink_lang::mock::MockState::new(ctx),
+        ));
+
+        #mock_trait
+
+        #mock_impl
+
+        #(#proxies)*
+
+        // Methods that are not present in the mock are handled by the fallback.
+        let implemented: &[u32] = &[#(#hashes),*];
+
+        <dyn #trait_name as ::obce::codegen::ExtensionDescription>::for_each_method(&mut |ext_id, method| {
+            if !implemented.contains(&method.hash) {
+                ::obce::ink_lang::env::test::register_chain_extension(
+                    ::obce::ink_lang::mock::FallbackProxy::<dyn #trait_name, #item>::new(
+                        ext_id,
+                        method,
+                        wrapped_context.clone(),
+                    ),
+                );
+            }
+        });
 
-            #(#proxies)*
+        ::obce::ink_lang::mock::MockHandle::new(wrapped_context)
+    };
+
+    let register = if mock_attrs.register_fn {
+        let snake_trait = to_snake_case(trait_ident);
+
+        // Named mocks are also available as `register_{mock}`.
+        let (register_name, alias) = match mock_ident {
+            Some(mock_ident) => {
+                let snake_mock = to_snake_case(mock_ident);
+
+                (
+                    format_ident!("register_{}_{}", snake_mock, snake_trait),
+                    Some(format_ident!("register_{}", snake_mock)),
+                )
+            }
+            None => (format_ident!("register_{}", snake_trait), None),
+        };
+
+        let aliases_mod = format_ident!("__obce_{}", register_name);
+        let aliases = iter::once(format_ident!("register_chain_extensions"))
+            .chain(alias)
+            .filter(|alias| alias != &register_name);
+
+        // Aliases are glob-imported, so that several mocks in the same module
+        // only fail to compile when an ambiguous alias is actually used.
+        quote! {
+            pub fn #register_name #types (ctx: #item) -> ::obce::ink_lang::mock::MockHandle<dyn #trait_name, #item> {
+                #register_body
+            }
+
+            #[doc(hidden)]
+            #[allow(unused_imports)]
+            mod #aliases_mod {
+                #(pub use super::#register_name as #aliases;)*
+            }
+
+            #[allow(unused_imports)]
+            pub use self::#aliases_mod::*;
+        }
+    } else {
+        quote! {
+            impl #impls ::obce::ink_lang::Mock<dyn #trait_name> for #item #where_clause {
+                fn register(self) -> ::obce::ink_lang::mock::MockHandle<dyn #trait_name, #item> {
+                    let ctx = self;
+
+                    #register_body
+                }
+            }
         }
+    };
+
+    let expectations_doc = format!("Expectations and recorded calls of mocked `{trait_ident}` methods.");
+
+    Ok(quote! {
+        #[doc = #expectations_doc]
+        pub trait #expectations_name {
+            #(#expectations_signatures)*
+        }
+
+        impl #impls #expectations_name for ::obce::ink_lang::mock::MockHandle<dyn #trait_name, #item> #where_clause {
+            #(#expectations_impls)*
+        }
+
+        #register
     })
 }
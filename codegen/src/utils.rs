@@ -51,6 +51,48 @@ macro_rules! format_err_spanned {
     }
 }
 
+/// Find `id = ...` attribute argument, given either as an integer or as a string to hash.
+pub fn find_id<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Result<Option<u16>, syn::Error> {
+    iter.into_iter()
+        .find_map(|arg| {
+            match arg {
+                NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("id") => {
+                    Some(match &value.lit {
+                        Lit::Int(lit_int) => lit_int.base10_parse::<u16>(),
+                        Lit::Str(lit_str) => Ok(into_u16(lit_str.value())),
+                        _ => Err(format_err_spanned!(value, "id should be integer or string")),
+                    })
+                }
+                _ => None,
+            }
+        })
+        .transpose()
+}
+
+/// Find `legacy_ids(...)` attribute argument, with every identifier given
+/// either as an integer or as a string to hash.
+pub fn find_legacy_ids<'a, I: IntoIterator<Item = &'a NestedMeta>>(iter: I) -> Result<Vec<u16>, syn::Error> {
+    let Some(list) = iter.into_iter().find_map(|arg| {
+        match arg {
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("legacy_ids") => Some(list),
+            _ => None,
+        }
+    }) else {
+        return Ok(Vec::new())
+    };
+
+    list.nested
+        .iter()
+        .map(|nested| {
+            match nested {
+                NestedMeta::Lit(Lit::Int(lit_int)) => lit_int.base10_parse::<u16>(),
+                NestedMeta::Lit(Lit::Str(lit_str)) => Ok(into_u16(lit_str.value())),
+                _ => Err(format_err_spanned!(nested, "legacy id should be integer or string")),
+            }
+        })
+        .try_collect()
+}
+
 pub fn into_u16<T: ToString>(ident: T) -> u16 {
     let mut output = [0; 32];
     blake2b_256(ident.to_string().as_bytes(), &mut output);
@@ -63,6 +105,42 @@ pub fn into_u32<T: ToString>(ident: T) -> u32 {
     u32::from_be_bytes([output[0], output[1], output[2], output[3]])
 }
 
+pub fn to_upper_camel_case<T: ToString>(ident: T) -> String {
+    ident
+        .to_string()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+pub fn to_snake_case<T: ToString>(ident: T) -> String {
+    let ident = ident.to_string();
+    let chars = ident.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(ident.len());
+
+    for (idx, ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next_is_lower = matches!(chars.get(idx + 1), Some(next) if next.is_lowercase());
+
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                output.push('_');
+            }
+        }
+
+        output.extend(ch.to_lowercase());
+    }
+
+    output
+}
+
 pub fn blake2b_256(input: &[u8], output: &mut [u8; 32]) {
     use ::blake2::digest::{
         consts::U32,
@@ -228,7 +306,28 @@ mod tests {
         Token,
     };
 
-    use super::InputBindings;
+    use super::{
+        to_snake_case,
+        to_upper_camel_case,
+        InputBindings,
+    };
+
+    #[test]
+    fn upper_camel_case_conversion() {
+        assert_eq!(to_upper_camel_case("method"), "Method");
+        assert_eq!(to_upper_camel_case("fetch_random"), "FetchRandom");
+        assert_eq!(to_upper_camel_case("_private__method_"), "PrivateMethod");
+        assert_eq!(to_upper_camel_case("alreadyCamel"), "AlreadyCamel");
+    }
+
+    #[test]
+    fn snake_case_conversion() {
+        assert_eq!(to_snake_case("Rand"), "rand");
+        assert_eq!(to_snake_case("PalletAssets"), "pallet_assets");
+        assert_eq!(to_snake_case("HTTPRequest"), "http_request");
+        assert_eq!(to_snake_case("Erc20"), "erc20");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+    }
 
     #[test]
     fn special_bindings_conversion() {
@@ -3,15 +3,12 @@
 #[cfg(feature = "substrate")]
 pub mod substrate;
 
-#[cfg(feature = "ink")]
-pub mod ink;
-
 #[obce::error]
 pub enum RandomReadErr {
     FailGetRandomSource,
 }
 
-#[obce::definition(id = "rand-extension@v0.1")]
+#[obce::definition(id = "rand-extension@v0.1", ink_struct = "Extension")]
 pub trait RandExtension {
     fn fetch_random(&self, subject: [u8; 32]) -> Result<[u8; 32], RandomReadErr>;
 }
@@ -2,7 +2,7 @@
 
 #[ink::contract]
 mod test_contract {
-    use rand_extension::{ink::Extension, RandExtension, RandomReadErr};
+    use rand_extension::{Extension, RandExtension, RandomReadErr};
 
     #[ink(storage)]
     pub struct TestContract {
@@ -25,6 +25,7 @@ use proc_macro::TokenStream;
 
 use obce_codegen::{
     definition,
+    environment,
     error,
     extension,
     hash,
@@ -44,6 +45,11 @@ use obce_codegen::{
 /// The behaviour of [`#[obce::definition]`](macro@definition) with `substrate` feature enabled
 /// is to leave everything as-is, without any additional modifications.
 ///
+/// With both features enabled (for example, when testing your implementation with
+/// `obce::substrate::ink::InkAdapter`), only the Substrate trait is generated,
+/// and ink! smart contracts should call chain extension methods using
+/// `ChainExtensionMethod` with identifiers from `obce::id!`.
+///
 /// ```ignore
 /// pub struct ChainExtension;
 ///
@@ -60,6 +66,274 @@ use obce_codegen::{
 /// correspondingly.
 ///
 /// `id` accepts literals of type [`&str`] and [`u16`].
+///
+/// By default, method identifiers are derived from method names only, so changing argument
+/// or output types keeps identifiers the same. With `#[obce::definition(id_from_signature)]`,
+/// method identifiers are derived from the method name, argument types and output type instead
+/// (for example, `fetch_random(u64,Vec<u8>)->[u8;32]`), so that any signature change results
+/// in a new identifier:
+///
+/// ```ignore
+/// #[obce::definition(id_from_signature)]
+/// pub trait ChainExtensionDefinition {
+///     fn some_method(&self, argument: u32) -> u64;
+/// }
+/// ```
+///
+/// Types are hashed as written, ignoring whitespace, which means that, for example, changing `u32`
+/// to `core::primitive::u32` changes the identifier too. Identifiers set explicitly with `#[obce(id = ...)]`,
+/// either as numbers or as strings, are kept as is. Signatures of computed identifiers are available
+/// via `obce::codegen::MethodInfo::signature`, and are included in the exported metadata,
+/// so that `obce-compat` reports signature changes.
+///
+/// # Versioning
+///
+/// When a method signature changes, deployed contracts still call it with the previous identifier
+/// and the previous input encoding. To serve both versions at once, give the changed method a new identifier
+/// and list the previous ones in `#[obce(legacy_ids(...))]`:
+///
+/// ```ignore
+/// #[obce::definition(id = "assets", version = 2)]
+/// pub trait AssetsExtension {
+///     #[obce(id = "transfer_v2", legacy_ids("transfer"))]
+///     fn transfer(&mut self, to: [u8; 32], amount: u128, memo: Vec<u8>) -> Result<(), Error>;
+/// }
+/// ```
+///
+/// ink! smart contracts always call the latest method identifier, while
+/// [`#[obce::implementation]`](macro@implementation) dispatches legacy identifiers
+/// to adapter methods, or to the method itself if the encoding didn't change.
+///
+/// `version` is available via `obce::codegen::ExtensionDescription::VERSION` and in the exported metadata,
+/// where increasing it acknowledges intentional breaking changes. It defaults to `1`.
+///
+/// # Supertraits
+///
+/// Chain extension definitions can inherit methods of other definitions, which allows
+/// a family of chain extensions to share a common base API:
+///
+/// ```ignore
+/// #[obce::definition(id = "assets")]
+/// pub trait AssetsExtV1 {
+///     fn balance_of(&self, owner: [u8; 32]) -> u128;
+/// }
+///
+/// #[obce::definition(id = "assets")]
+/// pub trait AssetsExtV2: AssetsExtV1 {
+///     fn transfer(&mut self, to: [u8; 32], amount: u128) -> Result<(), Error>;
+/// }
+/// ```
+///
+/// Inherited methods keep the method identifiers and the chain extension identifier of the supertrait.
+/// If both definitions share the chain extension identifier, as above, inherited methods are dispatched
+/// by the implementation of the inheriting definition, and method identifiers of both definitions must not clash.
+/// Otherwise, ink! smart contracts call inherited methods under the chain extension identifier of the supertrait,
+/// which should be registered in the runtime on its own.
+///
+/// Inherited methods are available via `obce::codegen::ExtensionDescription::for_each_method`,
+/// while `METHODS`, the call enum and the exported metadata only cover methods of the definition itself.
+///
+/// # Associated types
+///
+/// ink! smart contracts and the runtime use different types for the same concepts,
+/// such as `ink::primitives::AccountId` and `T::AccountId`. Definitions can declare such types
+/// as associated types, which are resolved to concrete types on each side:
+///
+/// ```ignore
+/// #[obce::definition(id = "assets")]
+/// pub trait AssetsExtension {
+///     type AccountId;
+///
+///     type Balance;
+///
+///     #[obce(ink = "u32", substrate = "u32")]
+///     type AssetId;
+///
+///     fn balance_of(&self, asset: Self::AssetId, owner: Self::AccountId) -> Self::Balance;
+/// }
+/// ```
+///
+/// On the ink! side, `AccountId`, `Balance`, `Hash`, `Timestamp` and `BlockNumber` resolve to
+/// the types of `ink::env::DefaultEnvironment`, or of the environment provided with
+/// `#[obce::definition(ink_environment = "path::to::Environment")]`.
+///
+/// On the Substrate side, the trait becomes generic over `T: pallet_contracts::Config`,
+/// and `AccountId`, `Hash` and `BlockNumber` resolve to the types of `frame_system::Config`,
+/// while `Balance` resolves to `obce::substrate::BalanceOf<T>`:
+///
+/// ```ignore
+/// #[obce::implementation]
+/// impl<'a, E, T, Env> AssetsExtension<T> for ExtensionContext<'a, E, T, Env, AssetsChainExtension>
+/// where
+///     T: SysConfig + ContractConfig,
+///     Env: ChainExtensionEnvironment<E, T>,
+/// {
+///     fn balance_of(&self, asset: u32, owner: T::AccountId) -> BalanceOf<T> {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// Other associated types should be resolved explicitly with `#[obce(ink = ..., substrate = ...)]`,
+/// where the Substrate type can refer to `T`. Method identifiers are derived from the signatures
+/// as written in the definition, so they are the same on both sides.
+///
+/// # Borrowed arguments
+///
+/// To avoid cloning data before every chain extension call, definitions can take borrowed arguments,
+/// which ink! smart contracts encode directly:
+///
+/// ```ignore
+/// #[obce::definition]
+/// pub trait StorageExtension {
+///     fn store(&mut self, key: &str, #[obce(owned = "BoundedVec<u8, ConstU32<64>>")] value: &[u8]);
+/// }
+/// ```
+///
+/// The chain extension decodes borrowed arguments into owned types with the same encoding:
+/// `&[T]` into `Vec<T>`, `&str` into `String` and `&T` into `T`. On the Substrate side,
+/// `#[obce(owned = ...)]` can declare another owned type, such as `BoundedVec`.
+/// Implementations and mocks take owned types, which are also used in `obce::codegen::MethodDescription::Input`,
+/// the call enum and the exported metadata:
+///
+/// ```ignore
+/// #[obce::implementation]
+/// impl<'a, E, T, Env> StorageExtension for ExtensionContext<'a, E, T, Env, StorageChainExtension>
+/// where
+///     T: SysConfig + ContractConfig,
+///     Env: ChainExtensionEnvironment<E, T>,
+/// {
+///     fn store(&mut self, key: String, value: BoundedVec<u8, ConstU32<64>>) {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// # ink!-only helpers
+///
+/// Methods marked with `#[obce(ink_only)]` are convenience helpers for ink! smart contracts,
+/// which can compose chain extension methods in their default implementations:
+///
+/// ```ignore
+/// #[obce::definition]
+/// pub trait AssetsExtension {
+///     fn balance_of(&self, owner: [u8; 32]) -> u128;
+///
+///     #[obce(ink_only)]
+///     fn has_balance(&self, owner: [u8; 32]) -> bool {
+///         self.balance_of(owner) > 0
+///     }
+/// }
+/// ```
+///
+/// ink!-only helpers don't have method identifiers and are absent from the Substrate side,
+/// so implementations and mocks don't provide them. They are also not listed in `METHODS`,
+/// the call enum and the exported metadata.
+///
+/// # ink! extension struct
+///
+/// With `#[obce::definition(ink_struct = "Extension")]`, the definition also generates
+/// an `Extension` unit struct for ink! smart contracts, marked with
+/// [`#[obce::ink_lang::extension]`](macro@ink_extension) and implementing the definition:
+///
+/// ```ignore
+/// #[obce::definition(id = "rand-extension@v0.1", ink_struct = "Extension")]
+/// pub trait RandExtension {
+///     fn fetch_random(&self, subject: [u8; 32]) -> Result<[u8; 32], RandomReadErr>;
+/// }
+///
+/// // In ink! smart contracts:
+/// let random = Extension.fetch_random(subject);
+/// ```
+///
+/// The struct derives `scale::Encode` and `scale::Decode` and, with `std` feature of your crate enabled,
+/// `scale_info::TypeInfo` and `ink::storage::traits::StorageLayout`, so it can be stored in contracts.
+/// This requires your crate to depend on `scale-info` and `ink`.
+///
+/// # Call enum
+///
+/// For non-generic traits, [`#[obce::definition]`](macro@definition) also generates
+/// a `{TraitName}Call` enum with a variant per method, holding typed method inputs:
+///
+/// ```ignore
+/// let call = ChainExtensionDefinitionCall::SomeMethod(123);
+///
+/// // Method identifier and raw input, as passed by ink! smart contracts.
+/// let (func_id, input) = (call.func_id(), call.encode_input());
+///
+/// assert!(ChainExtensionDefinitionCall::decode_input(func_id, &input).is_ok());
+///
+/// // Call the method on any trait implementor, receiving the encoded output.
+/// let output = call.dispatch(&mut extension);
+/// ```
+///
+/// Call enums implement `scale::Encode` and `scale::Decode`, which
+/// makes them suitable for logging, batching and replaying chain extension calls.
+/// Additional traits can be derived with `call_derive`, as long as
+/// every method argument type implements them:
+///
+/// ```ignore
+/// #[obce::definition(call_derive(Debug, Clone, PartialEq, Eq))]
+/// pub trait ChainExtensionDefinition {
+///     fn some_method(&self, argument: u32) -> u64;
+/// }
+/// ```
+///
+/// Definitions with associated types only have a call enum with `ink` feature enabled,
+/// which holds ink! types.
+///
+/// # Reflection
+///
+/// Chain extension name and information about every method (identifiers, argument names,
+/// mutability and documentation) are available via `obce::codegen::ExtensionDescription`:
+///
+/// ```ignore
+/// use obce::codegen::ExtensionDescription;
+///
+/// for method in <dyn ChainExtensionDefinition as ExtensionDescription>::METHODS {
+///     println!("{}::{} ({})", <dyn ChainExtensionDefinition as ExtensionDescription>::NAME, method.name, method.id);
+/// }
+/// ```
+///
+/// With `std` feature of your crate enabled, `obce::codegen::MethodTypes` additionally provides
+/// `scale_info` type information of method inputs and outputs, as well as `ret_val` status codes
+/// of method errors.
+///
+/// # Metadata
+///
+/// With `metadata` feature of OBCE enabled, you can use `obce::metadata::ExtensionMetadata`
+/// to export portable chain extension metadata (identifiers, input and output types
+/// in a `scale_info` registry, and `ret_val` tables) as JSON, for example from a build script or a test:
+///
+/// ```ignore
+/// use obce::metadata::ExtensionMetadata;
+///
+/// <dyn ChainExtensionDefinition as ExtensionMetadata>::metadata()
+///     .write("metadata.json")
+///     .unwrap();
+/// ```
+///
+/// Exported metadata files can be compared with `obce::metadata::compat` module or `obce-compat` binary
+/// to detect changes that break deployed contracts. Removed methods and identifiers are always reported,
+/// while argument, return and status code type changes are allowed with a chain extension version bump.
+/// For example, you can keep the metadata of
+/// the released version in your repository, and fail CI on incompatible changes:
+///
+/// ```ignore
+/// use obce::metadata::{
+///     compat::assert_compatible,
+///     ExtensionMetadata,
+///     Metadata,
+/// };
+///
+/// #[test]
+/// fn compatible_with_released_version() {
+///     assert_compatible(
+///         &Metadata::read("released.json").unwrap(),
+///         &<dyn ChainExtensionDefinition as ExtensionMetadata>::metadata(),
+///     );
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn definition(attrs: TokenStream, trait_item: TokenStream) -> TokenStream {
     match definition::generate(attrs.into(), trait_item.into()) {
@@ -202,6 +476,69 @@ pub fn definition(attrs: TokenStream, trait_item: TokenStream) -> TokenStream {
 /// ```
 ///
 /// This is done to ease chain extension environment generalization during testing.
+///
+/// # Legacy identifiers
+///
+/// Methods listed in `#[obce(legacy_ids(...))]` of the definition keep serving their previous identifiers.
+/// If the previous input or output encoding is different, add an adapter method marked with
+/// `#[obce(legacy(id = ...))]`, that accepts and returns the previous types:
+///
+/// ```ignore
+/// #[obce::implementation]
+/// impl<'a, E, T, Env> AssetsExtension for ExtensionContext<'a, E, T, Env, ChainExtension>
+/// where
+///     T: SysConfig + ContractConfig,
+///     <<T as SysConfig>::Lookup as StaticLookup>::Source: From<<T as SysConfig>::AccountId>,
+///     Env: ChainExtensionEnvironment<E, T>,
+/// {
+///     fn transfer(&mut self, to: [u8; 32], amount: u128, memo: Vec<u8>) -> Result<(), Error> {
+///         // ...
+///     }
+///
+///     #[obce(legacy(id = "transfer"))]
+///     fn transfer_v1(&mut self, to: [u8; 32], amount: u128) -> Result<(), Error> {
+///         self.transfer(to, amount, Vec::new())
+///     }
+/// }
+/// ```
+///
+/// Adapter methods are not a part of the chain extension trait, and are only available to the dispatcher.
+/// They support the same `weight` and `ret_val` attributes as regular methods, and can only serve identifiers
+/// listed in `legacy_ids` of the definition, which is checked at compile time for non-generic definitions.
+/// Legacy identifiers without an adapter are dispatched to the method itself.
+///
+/// # Supertraits
+///
+/// If your chain extension definition inherits methods of other definitions, implement the supertraits
+/// with `#[obce::implementation(supertrait)]`. Such implementations only dispatch their own methods,
+/// and are called by the implementation of the inheriting definition, which is the one registered in the runtime:
+///
+/// ```ignore
+/// #[obce::implementation(supertrait)]
+/// impl<'a, E, T, Env> AssetsExtV1 for ExtensionContext<'a, E, T, Env, ChainExtension>
+/// where
+///     T: SysConfig + ContractConfig,
+///     Env: ChainExtensionEnvironment<E, T>,
+/// {
+///     fn balance_of(&self, owner: [u8; 32]) -> u128 {
+///         // ...
+///     }
+/// }
+///
+/// #[obce::implementation]
+/// impl<'a, E, T, Env> AssetsExtV2 for ExtensionContext<'a, E, T, Env, ChainExtension>
+/// where
+///     T: SysConfig + ContractConfig,
+///     Env: ChainExtensionEnvironment<E, T>,
+/// {
+///     fn transfer(&mut self, to: [u8; 32], amount: u128) -> Result<(), Error> {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// Supertraits with their own chain extension identifiers are dispatched only for calls
+/// with the matching chain extension identifier.
 #[proc_macro_attribute]
 pub fn implementation(attrs: TokenStream, impl_item: TokenStream) -> TokenStream {
     match implementation::generate(attrs.into(), impl_item.into()) {
@@ -253,6 +590,9 @@ pub fn implementation(attrs: TokenStream, impl_item: TokenStream) -> TokenStream
 /// [`TryFrom<YourError>`](::core::convert::TryFrom) for `pallet_contracts::chain_extension::RetVal`,
 /// which will automatically convert suitable error variants to `RetVal` on implementation methods marked with `#[obce(ret_val)]`.
 ///
+/// The same mapping is available regardless of the activated features via `obce::codegen::ErrorDescription`,
+/// which is used by [`#[obce::mock]`](macro@mock) to produce status codes.
+///
 /// Error variant's `#[obce(ret_val = "...")]` accepts an expression that evaluates to [`u32`]:
 ///
 /// ```ignore
@@ -324,6 +664,239 @@ pub fn error(attrs: TokenStream, enum_item: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// # Unimplemented methods
+///
+/// Every method of your chain extension definition is registered, even if it is not
+/// present in the `impl` block. By default, calls to unimplemented methods panic with the method name.
+///
+/// You can change this behaviour using `set_fallback` method of the returned handle:
+///
+/// ```ignore
+/// use obce::ink_lang::mock::Fallback;
+///
+/// let mock = register_chain_extensions(());
+///
+/// // Return `Default` value of the method output type.
+/// mock.set_fallback(Fallback::Default);
+///
+/// // Return `Err(Error::NotSupported)`, or its status code if the variant
+/// // is marked with `#[obce(ret_val = "...")]`.
+/// mock.set_fallback(Fallback::error(Error::NotSupported));
+///
+/// // Handle the call manually, returning SCALE-encoded output.
+/// mock.set_fallback(Fallback::handler(|func_id, input| input.to_vec()));
+///
+/// // Charge weight for every call to unimplemented methods.
+/// mock.set_fallback_weight(Weight::from_parts(1_000, 0));
+/// ```
+///
+/// # Status codes
+///
+/// Similarly to [`#[obce::implementation]`](macro@implementation), you can mark mocked methods
+/// with `#[obce(ret_val)]`. Errors that have `#[obce(ret_val = "...")]` specified
+/// are then returned as a status code, and nothing is written to the output buffer,
+/// exactly as the runtime does it:
+///
+/// ```ignore
+/// #[obce::error]
+/// pub enum Error {
+///     #[obce(ret_val = "100")]
+///     One,
+/// }
+///
+/// #[obce::definition]
+/// pub trait MyChainExtension {
+///     fn method(&mut self) -> Result<u32, Error>;
+/// }
+///
+/// #[obce::mock]
+/// impl MyChainExtension for () {
+///     #[obce(ret_val)]
+///     fn method(&mut self) -> Result<u32, Error> {
+///         // Contract receives status code 100.
+///         Err(Error::One)
+///     }
+/// }
+/// ```
+///
+/// # Critical errors
+///
+/// On-chain, critical errors abort the contract call. To simulate this behaviour,
+/// mocked methods can return `Result<Output, obce::ink_lang::Trap>` instead of `Output`.
+/// The returned trap unwinds the contract call, and can be caught
+/// with `obce::ink_lang::mock::catch_trap`. Note that, unlike on-chain, contract state
+/// changes made before the trap are not reverted:
+///
+/// ```ignore
+/// use obce::ink_lang::{mock::catch_trap, Trap};
+///
+/// #[obce::mock]
+/// impl MyChainExtension for () {
+///     fn first_method(&mut self, val: u32) -> Result<u32, Trap> {
+///         Err(Trap("not available"))
+///     }
+/// }
+///
+/// #[test]
+/// fn call_contract() {
+///     register_chain_extensions(());
+///     let mut contract = crate::simple_contract::SimpleContract::new();
+///     assert_eq!(catch_trap(|| contract.call_first_method(1)), Err(Trap("not available")));
+/// }
+/// ```
+///
+/// # Weight
+///
+/// Mocked methods can declare their weight using the same `#[obce(weight(expr = ...))]`
+/// attribute as [`#[obce::implementation]`](macro@implementation). Weight expressions use
+/// `obce::ink_lang::mock::Weight`, which is the Substrate type itself when the `substrate`
+/// feature is enabled, and a mirror of its API otherwise.
+///
+/// Charged weight is available via `consumed_weight` method of the returned handle.
+/// You can also limit the total weight with `set_gas_limit`, in which case calls that
+/// exceed the limit abort the contract call with `obce::ink_lang::mock::OUT_OF_GAS` trap:
+///
+/// ```ignore
+/// use obce::ink_lang::mock::Weight;
+///
+/// #[obce::mock]
+/// impl MyChainExtension for () {
+///     #[obce(weight(expr = "Weight::from_parts(*val as u64, 0)"))]
+///     fn first_method(&mut self, val: u32) -> u32 {
+///         val
+///     }
+/// }
+///
+/// #[test]
+/// fn call_contract() {
+///     let mock = register_chain_extensions(());
+///     mock.set_gas_limit(Weight::from_parts(1_000, 0));
+///     // Call the contract...
+///     assert_eq!(mock.consumed_weight(), Weight::from_parts(100, 0));
+/// }
+/// ```
+///
+/// # Recorded calls and expectations
+///
+/// `register_chain_extensions` returns an `obce::ink_lang::mock::MockHandle`,
+/// which records every call received by the mocked chain extension.
+///
+/// [`#[obce::mock]`](macro@mock) also generates a `{Trait}Expectations` trait
+/// (`{Mock}{Trait}Expectations` for mocks of named types) for the returned handle, which contains `expect_{method}` and `{method}_calls`
+/// methods for every mocked method. Expectations can constrain the received arguments,
+/// the number of calls, and provide return values:
+///
+/// ```ignore
+/// #[obce::mock]
+/// impl MyChainExtension for () {
+///     fn first_method(&mut self, val: u32) -> u32 {
+///         val
+///     }
+///
+///     // Methods without a body can only be called through expectations.
+///     fn second_method(&mut self) -> u64;
+/// }
+///
+/// #[test]
+/// fn call_contract() {
+///     let mock = register_chain_extensions(());
+///     mock.expect_first_method().with(1).times(2).returning(|val| val * 10);
+///     mock.expect_second_method().return_const(5);
+///
+///     // Call the contract...
+///
+///     assert_eq!(mock.first_method_calls(), vec![1, 1]);
+/// }
+/// ```
+///
+/// Expectations are checked in order, and the first one that matches the arguments is used.
+/// If a matching expectation has no return value, the method body is called instead.
+///
+/// Unsatisfied `times` constraints are reported when the handle is dropped
+/// or when `MockHandle::checkpoint` is called.
+///
+/// # Multiple chain extensions
+///
+/// By default, [`#[obce::mock]`](macro@mock) generates a `register_{trait}` function
+/// (`register_{mock}_{trait}` for mocks of named types), which is also available
+/// as `register_chain_extensions` and `register_{mock}`. Several mocks can be declared
+/// in the same module, in which case only the aliases they share become ambiguous:
+///
+/// ```ignore
+/// #[obce::mock]
+/// impl RandExtension for RandContext {
+///     fn fetch_random(&mut self, subject: [u8; 32]) -> [u8; 32] {
+///         subject
+///     }
+/// }
+///
+/// #[obce::mock]
+/// impl BalancesExtension for () {
+///     fn balance(&self, account: AccountId) -> u128 {
+///         0
+///     }
+/// }
+///
+/// #[test]
+/// fn call_contract() {
+///     let rand = register_rand_context(RandContext);
+///     let balances = register_balances_extension(());
+/// }
+/// ```
+///
+/// Alternatively, use `#[obce::mock(register_fn = false)]`.
+/// Instead of a function, the mock context then implements `obce::ink_lang::Mock` trait
+/// for your chain extension definition:
+///
+/// ```ignore
+/// #[obce::mock(register_fn = false)]
+/// impl RandExtension for RandContext {
+///     fn fetch_random(&mut self, subject: [u8; 32]) -> [u8; 32] {
+///         subject
+///     }
+/// }
+///
+/// #[obce::mock(register_fn = false)]
+/// impl BalancesExtension for BalancesContext {
+///     fn balance(&self, account: AccountId) -> u128 {
+///         0
+///     }
+/// }
+///
+/// #[test]
+/// fn call_contract() {
+///     let rand = obce::ink_lang::Mock::<dyn RandExtension>::register(RandContext);
+///
+///     // Or register multiple mocks at once.
+///     let (rand, balances) = obce::register_mocks!(
+///         RandContext => dyn RandExtension,
+///         BalancesContext => dyn BalancesExtension,
+///     );
+/// }
+/// ```
+///
+/// # Supertraits
+///
+/// Mocks of chain extension definitions with supertraits can implement inherited methods
+/// alongside the methods of the definition itself. Inherited methods are registered with the method
+/// and chain extension identifiers of the supertrait that defines them:
+///
+/// ```ignore
+/// #[obce::mock]
+/// impl AssetsExtV2 for AssetsContext {
+///     // Inherited from `AssetsExtV1`.
+///     fn balance_of(&self, owner: [u8; 32]) -> u128 {
+///         self.balances.get(&owner).copied().unwrap_or_default()
+///     }
+///
+///     fn transfer(&mut self, to: [u8; 32], amount: u128) -> Result<(), Error> {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// Unimplemented inherited methods are handled by the fallback, same as other methods.
+///
 /// # Context
 ///
 /// The item that you implement your definition trait for becomes your testing context.
@@ -331,6 +904,47 @@ pub fn error(attrs: TokenStream, enum_item: TokenStream) -> TokenStream {
 /// You will receive the same testing context when calling methods multiple times,
 /// thus it can be used as your chain extension testing state.
 ///
+/// The handle returned by `register_chain_extensions` shares the testing context
+/// with the registered mocks. Use `borrow` and `borrow_mut` to inspect or modify it
+/// between contract calls, and `replace` or `reset` to swap it mid-test:
+///
+/// ```ignore
+/// let mock = register_chain_extensions(State::default());
+/// // Call the contract...
+/// assert_eq!(mock.borrow().call_count, 1);
+/// mock.reset();
+/// ```
+///
+/// # Environment
+///
+/// Mocked methods can't access ink! test environment directly, since it is borrowed
+/// by ink! for the duration of the chain extension call. Instead, use `obce::ink_lang::mock::MockEnv`,
+/// which provides caller, callee, block number and timestamp captured right before the call:
+///
+/// ```ignore
+/// use obce::ink_lang::mock::MockEnv;
+///
+/// #[obce::mock]
+/// impl MyChainExtension for State {
+///     fn first_method(&mut self, val: u32) -> u32 {
+///         *self.balances.entry(MockEnv::caller()).or_default() += val;
+///         val
+///     }
+/// }
+/// ```
+///
+/// Caller and callee are only captured after the test calls `MockEnv::capture_accounts()`,
+/// since ink! panics when reading accounts that were not set in the test environment.
+///
+/// The environment is only captured with the `ink-mock` feature of OBCE enabled,
+/// which you should activate for tests only (for example, via `dev-dependencies`).
+/// For definitions with a custom `ink_environment`, use `MockEnv::caller_of::<Environment>()`
+/// and similar methods to get values of the environment types.
+///
+/// In multi-contract tests, all contracts share the same registered mocks.
+/// To keep a separate state for each contract, use `obce::ink_lang::mock::PerCallee`,
+/// which stores values keyed by the callee contract.
+///
 /// # General guidelines
 ///
 /// Since [`#[obce::mock]`](macro@mock) is designed for off-chain testing, you are
@@ -424,6 +1038,9 @@ pub fn mock(attrs: TokenStream, enum_item: TokenStream) -> TokenStream {
 ///
 /// impl Trait for TestExtension {}
 /// ```
+///
+/// Such structs can also be generated by [`#[obce::definition]`](macro@definition)
+/// with `ink_struct` attribute.
 #[proc_macro_attribute]
 pub fn ink_extension(attrs: TokenStream, struct_item: TokenStream) -> TokenStream {
     match extension::ink(attrs.into(), struct_item.into()) {
@@ -432,6 +1049,80 @@ pub fn ink_extension(attrs: TokenStream, struct_item: TokenStream) -> TokenStrea
     }
 }
 
+/// ink! environment with multiple chain extensions.
+///
+/// # Description
+///
+/// ink! environments support only a single `ChainExtension` type, which is available
+/// via `self.env().extension()`. Using this macro, you can generate an environment
+/// based on an existing one (for example, `DefaultEnvironment`), with a chain extension type
+/// that provides accessors for each of the provided chain extensions.
+///
+/// Accessor names are derived from extension type names, with `Extension` suffix removed
+/// and converted to snake case (`RandExtension` becomes `rand`). You can also provide
+/// accessor names explicitly using `name: Extension` syntax.
+///
+/// Each extension should implement `ChainExtensionInstance`, for example by using
+/// [`#[obce::ink_lang::extension]`](macro@ink_extension) or `ink_struct` attribute
+/// of [`#[obce::definition]`](macro@definition).
+///
+/// The chain extension type is generated alongside the environment with `Extensions` suffix
+/// (`MyEnvExtensions` for `MyEnv`).
+///
+/// # Example
+///
+/// ```ignore
+/// #[obce::definition(id = 1, ink_struct = "RandExtension")]
+/// pub trait Rand {
+///     fn fetch_random(&self, subject: [u8; 32]) -> [u8; 32];
+/// }
+///
+/// #[obce::definition(id = 2, ink_struct = "AssetsExtension")]
+/// pub trait Assets {
+///     fn total_supply(&self, asset_id: u32) -> u128;
+/// }
+///
+/// obce::ink_lang::environment!(
+///     pub MyEnv: obce::ink_lang::env::DefaultEnvironment,
+///     extensions = [RandExtension, pallet_assets: AssetsExtension],
+/// );
+///
+/// #[ink::contract(env = crate::MyEnv)]
+/// mod contract {
+///     use crate::{
+///         Assets,
+///         Rand,
+///     };
+///
+///     #[ink(storage)]
+///     pub struct Contract {}
+///
+///     impl Contract {
+///         #[ink(constructor)]
+///         pub fn new() -> Self {
+///             Contract {}
+///         }
+///
+///         #[ink(message)]
+///         pub fn random(&self, subject: [u8; 32]) -> [u8; 32] {
+///             self.env().extension().rand().fetch_random(subject)
+///         }
+///
+///         #[ink(message)]
+///         pub fn total_supply(&self, asset_id: u32) -> u128 {
+///             self.env().extension().pallet_assets().total_supply(asset_id)
+///         }
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn ink_environment(input: TokenStream) -> TokenStream {
+    match environment::ink(input.into()) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 /// Chain extension method hasher.
 ///
 /// # Description
@@ -0,0 +1,146 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Compare two chain extension metadata files, exported with `obce::metadata::ExtensionMetadata`.
+//!
+//! Usage: `obce-compat <old.json> <new.json>`.
+//!
+//! Exits with a non-zero status code if any method or identifier was removed, or if any breaking
+//! or error code change keeps affected identifiers in the new metadata without a chain extension version bump.
+
+use std::{
+    env,
+    process::ExitCode,
+};
+
+use obce::metadata::{
+    compat::{
+        self,
+        Change,
+        ChangeDetail,
+        ChangeKind,
+    },
+    Metadata,
+};
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    let [old, new] = &args[..] else {
+        eprintln!("usage: obce-compat <old.json> <new.json>");
+        return ExitCode::from(2)
+    };
+
+    let read = |path: &str| {
+        Metadata::read(path).unwrap_or_else(|error| {
+            eprintln!("unable to read metadata {path}: {error}");
+            std::process::exit(2)
+        })
+    };
+
+    let report = compat::compare(&read(old), &read(new));
+
+    if report.changes.is_empty() {
+        println!("no changes");
+    }
+
+    for change in &report.changes {
+        println!("{}", describe(change));
+    }
+
+    if report.unversioned().next().is_some() {
+        eprintln!("incompatible changes were found");
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn describe(change: &Change) -> String {
+    let kind = match change.kind {
+        ChangeKind::Compatible => "compatible",
+        ChangeKind::ErrorCodes => "error codes",
+        ChangeKind::Breaking => "breaking",
+    };
+
+    let optional = |value: &Option<String>| value.as_deref().unwrap_or("<unknown>").to_string();
+
+    let detail = match &change.detail {
+        ChangeDetail::ExtensionId { old, new } => format!("chain extension id changed from {old} to {new}"),
+        ChangeDetail::ExtensionVersion { old, new } => format!("chain extension version changed from {old} to {new}"),
+        ChangeDetail::MethodAdded => "method added".to_string(),
+        ChangeDetail::MethodRenamed { old, new } => format!("method renamed from `{old}` to `{new}`"),
+        ChangeDetail::MethodRemoved { legacy_by: None } => "method removed".to_string(),
+        ChangeDetail::MethodRemoved {
+            legacy_by: Some(legacy_by),
+        } => format!("method removed, its id is dispatched as legacy by `{legacy_by}`"),
+        ChangeDetail::MethodId {
+            old,
+            new,
+            legacy: false,
+        } => format!("method id changed from {old} to {new}"),
+        ChangeDetail::MethodId { old, new, legacy: true } => {
+            format!("method id changed from {old} to {new}, previous id is dispatched as legacy")
+        }
+        ChangeDetail::Signature { old, new } => {
+            format!("signature changed from `{}` to `{}`", optional(old), optional(new))
+        }
+        ChangeDetail::InputType { old, new } => {
+            format!("input type changed from `{}` to `{}`", optional(old), optional(new))
+        }
+        ChangeDetail::OutputType { old, new } => {
+            format!("output type changed from `{}` to `{}`", optional(old), optional(new))
+        }
+        ChangeDetail::Mutability { old, new } => format!("receiver mutability changed from {old} to {new}"),
+        ChangeDetail::Args { old, new } => {
+            format!("arguments renamed from ({}) to ({})", old.join(", "), new.join(", "))
+        }
+        ChangeDetail::StatusCode { variant, old, new } => {
+            match (old, new) {
+                (Some(old), Some(new)) => format!("`{variant}` status code changed from {old} to {new}"),
+                (Some(_), None) => format!("`{variant}` no longer has a status code"),
+                (None, Some(new)) => format!("`{variant}` now has status code {new}"),
+                (None, None) => format!("`{variant}` status code changed"),
+            }
+        }
+    };
+
+    let mut description = format!("[{kind}] ");
+
+    if let Some(method) = &change.method {
+        description += &format!("`{method}`: ");
+    }
+
+    description += &detail;
+
+    if !change.func_ids.is_empty() {
+        let ids = change
+            .func_ids
+            .iter()
+            .map(|id| format!("{id:#010x}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        description += &format!(" (affected ids: {ids})");
+    }
+
+    description
+}
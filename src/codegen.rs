@@ -19,12 +19,36 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
+use core::marker::PhantomData;
+
+pub use scale_info::MetaType;
+use scale_info::TypeInfo;
+
 /// Chain extension description.
 ///
 /// This trait is automatically implemented with `#[obce::definition]` macro expansion.
 pub trait ExtensionDescription {
+    /// Chain extension name, as written in the chain extension definition.
+    const NAME: &'static str;
+
     /// Unique chain extension identifier.
     const ID: u16;
+
+    /// Chain extension version, which can be set via `#[obce::definition(version = ...)]`.
+    ///
+    /// Defaults to `1`.
+    const VERSION: u16;
+
+    /// Information about every chain extension method, in definition order.
+    const METHODS: &'static [MethodInfo];
+
+    /// Call `f` with the chain extension identifier and information of every method,
+    /// including methods inherited from supertraits of the chain extension definition.
+    fn for_each_method(f: &mut dyn FnMut(u16, &'static MethodInfo)) {
+        for method in Self::METHODS {
+            f(Self::ID, method);
+        }
+    }
 }
 
 /// Chain extension method description.
@@ -35,9 +59,309 @@ pub trait MethodDescription<const METHOD_HASH: u32> {
     /// Unique chain extension method identifier.
     const ID: u16;
 
+    /// Previous method identifiers, which can be set via `#[obce(legacy_ids(...))]`.
+    ///
+    /// Calls with these identifiers are still dispatched by `#[obce::implementation]`,
+    /// while ink! smart contracts always use [`ID`](MethodDescription::ID).
+    const LEGACY_IDS: &'static [u16];
+
     /// Method input type, that is required for chain extension calls.
     type Input;
 
     /// Method output type, that you can use to obtain results from chain extension calls.
     type Output;
 }
+
+/// Chain extension definition that provides a method with the provided `METHOD_HASH`,
+/// either by itself or through one of its supertraits.
+///
+/// `Path` is inferred by the compiler, which allows macros to refer to inherited methods
+/// without knowing which supertrait defines them.
+///
+/// This trait is automatically implemented with `#[obce::definition]` macro expansion.
+pub trait MethodOwner<const METHOD_HASH: u32, Path> {
+    /// Chain extension definition that defines the method.
+    type Owner: ?Sized + ExtensionDescription + MethodDescription<METHOD_HASH>;
+}
+
+/// [`MethodOwner`] path of methods defined by the chain extension itself.
+pub struct Defined;
+
+/// [`MethodOwner`] path of methods inherited from the supertrait with the provided `INDEX`.
+pub struct Inherited<Path, const INDEX: usize>(PhantomData<Path>);
+
+impl<T, const METHOD_HASH: u32> MethodOwner<METHOD_HASH, Defined> for T
+where
+    T: ?Sized + ExtensionDescription + MethodDescription<METHOD_HASH>,
+{
+    type Owner = T;
+}
+
+/// Check if any of `ids` is used by `methods`, either as a current or a legacy identifier.
+#[doc(hidden)]
+pub const fn shares_method_id(methods: &[MethodInfo], ids: &[u16]) -> bool {
+    let mut method_idx = 0;
+
+    while method_idx < methods.len() {
+        let method = &methods[method_idx];
+
+        let mut id_idx = 0;
+
+        while id_idx < ids.len() {
+            if method.id == ids[id_idx] {
+                return true
+            }
+
+            let mut legacy_idx = 0;
+
+            while legacy_idx < method.legacy_ids.len() {
+                if method.legacy_ids[legacy_idx] == ids[id_idx] {
+                    return true
+                }
+
+                legacy_idx += 1;
+            }
+
+            id_idx += 1;
+        }
+
+        method_idx += 1;
+    }
+
+    false
+}
+
+/// Check if `id` is listed as a legacy identifier of any of `methods`.
+#[doc(hidden)]
+pub const fn is_legacy_id(methods: &[MethodInfo], id: u16) -> bool {
+    let mut method_idx = 0;
+
+    while method_idx < methods.len() {
+        let legacy_ids = methods[method_idx].legacy_ids;
+
+        let mut legacy_idx = 0;
+
+        while legacy_idx < legacy_ids.len() {
+            if legacy_ids[legacy_idx] == id {
+                return true
+            }
+
+            legacy_idx += 1;
+        }
+
+        method_idx += 1;
+    }
+
+    false
+}
+
+/// Chain extension error description.
+///
+/// This trait is automatically implemented with `#[obce::error]` macro expansion.
+pub trait ErrorDescription: Sized {
+    /// Status codes of error variants marked with `#[obce(ret_val = "...")]`.
+    const RET_VALS: &'static [RetValInfo];
+
+    /// Convert an error to a status code.
+    ///
+    /// Returns [`Ok`] with a status code if the error variant is marked
+    /// with `#[obce(ret_val = "...")]`, and [`Err`] with the original error otherwise.
+    fn try_into_ret_val(self) -> Result<u32, Self>;
+}
+
+/// Status code of an error variant, available via [`ErrorDescription::RET_VALS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetValInfo {
+    /// Error variant name.
+    pub variant: &'static str,
+
+    /// Status code, returned to the contract instead of the error.
+    pub ret_val: u32,
+}
+
+/// Chain extension method information, available via [`ExtensionDescription::METHODS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethodInfo {
+    /// Method name, as written in the chain extension definition.
+    pub name: &'static str,
+
+    /// Unique chain extension method identifier.
+    pub id: u16,
+
+    /// Previous method identifiers, see [`MethodDescription::LEGACY_IDS`].
+    pub legacy_ids: &'static [u16],
+
+    /// Method hash, which is used as `METHOD_HASH` in [`MethodDescription`].
+    pub hash: u32,
+
+    /// Argument names, excluding the receiver.
+    pub args: &'static [&'static str],
+
+    /// Whether the method accepts `&mut self`.
+    pub mutable: bool,
+
+    /// Documentation lines of the method.
+    pub docs: &'static [&'static str],
+
+    /// Normalized method signature (for example, `fetch_random(u64,Vec<u8>)->[u8;32]`),
+    /// if the identifier is computed from it with `#[obce::definition(id_from_signature)]`.
+    ///
+    /// Equals to [`None`] for identifiers computed from method names or set explicitly with `#[obce(id = ...)]`.
+    pub signature: Option<&'static str>,
+}
+
+/// Type information of chain extension method inputs and outputs.
+///
+/// This trait is automatically implemented with `#[obce::definition]` macro expansion
+/// when `std` feature of your crate is enabled.
+pub trait MethodTypes {
+    /// Type information of the input of a method with the provided hash,
+    /// if the input type implements [`TypeInfo`](scale_info::TypeInfo).
+    fn input_type(hash: u32) -> Option<MetaType>;
+
+    /// Type information of the output of a method with the provided hash,
+    /// if the output type implements [`TypeInfo`](scale_info::TypeInfo).
+    fn output_type(hash: u32) -> Option<MetaType>;
+
+    /// Status codes of the error returned by a method with the provided hash,
+    /// if the method returns [`Result`] with an error that implements [`ErrorDescription`].
+    fn ret_vals(hash: u32) -> &'static [RetValInfo];
+}
+
+#[doc(hidden)]
+pub struct TypeOf<T: ?Sized>(pub PhantomData<T>);
+
+impl<T> TypeOf<T>
+where
+    T: TypeInfo + 'static,
+{
+    #[inline]
+    // See `ToCriticalErr::try_to_critical_error`.
+    #[allow(dead_code)]
+    pub fn meta_type(&self) -> Option<MetaType> {
+        Some(MetaType::new::<T>())
+    }
+}
+
+#[doc(hidden)]
+pub trait TypeOfFallback {
+    fn meta_type(&self) -> Option<MetaType>;
+}
+
+impl<T: ?Sized> TypeOfFallback for TypeOf<T> {
+    #[inline]
+    fn meta_type(&self) -> Option<MetaType> {
+        None
+    }
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! meta_type {
+    ( $ty:ty $(,)? ) => {{
+        #[allow(unused_imports)]
+        use $crate::codegen::TypeOfFallback;
+        $crate::codegen::TypeOf::<$ty>(::core::marker::PhantomData).meta_type()
+    }};
+}
+
+#[doc(hidden)]
+pub struct RetValsOf<T: ?Sized>(pub PhantomData<T>);
+
+impl<T, E> RetValsOf<Result<T, E>>
+where
+    E: ErrorDescription,
+{
+    #[inline]
+    // See `ToCriticalErr::try_to_critical_error`.
+    #[allow(dead_code)]
+    pub fn ret_vals(&self) -> &'static [RetValInfo] {
+        E::RET_VALS
+    }
+}
+
+#[doc(hidden)]
+pub trait RetValsOfFallback {
+    fn ret_vals(&self) -> &'static [RetValInfo];
+}
+
+impl<T: ?Sized> RetValsOfFallback for RetValsOf<T> {
+    #[inline]
+    fn ret_vals(&self) -> &'static [RetValInfo] {
+        &[]
+    }
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! ret_vals {
+    ( $ty:ty $(,)? ) => {{
+        #[allow(unused_imports)]
+        use $crate::codegen::RetValsOfFallback;
+        $crate::codegen::RetValsOf::<$ty>(::core::marker::PhantomData).ret_vals()
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use scale_info::MetaType;
+
+    use super::{
+        shares_method_id,
+        ErrorDescription,
+        MethodInfo,
+        RetValInfo,
+    };
+
+    #[test]
+    fn meta_type_works_if_type_info_implemented() {
+        assert_eq!(meta_type!(u32), Some(MetaType::new::<u32>()));
+        assert_eq!(meta_type!((u8, bool)), Some(MetaType::new::<(u8, bool)>()));
+    }
+
+    #[test]
+    fn meta_type_works_if_type_info_is_not_implemented() {
+        struct NoTypeInfo;
+
+        assert_eq!(meta_type!(NoTypeInfo), None);
+    }
+
+    #[test]
+    fn ret_vals_of_error_description() {
+        struct Error;
+
+        impl ErrorDescription for Error {
+            const RET_VALS: &'static [RetValInfo] = &[RetValInfo {
+                variant: "Error",
+                ret_val: 1,
+            }];
+
+            fn try_into_ret_val(self) -> Result<u32, Self> {
+                Ok(1)
+            }
+        }
+
+        assert_eq!(ret_vals!(Result<(), Error>), Error::RET_VALS);
+        assert_eq!(ret_vals!(Result<(), u8>), &[]);
+        assert_eq!(ret_vals!(u32), &[]);
+    }
+
+    #[test]
+    fn shares_method_id_checks_current_and_legacy_ids() {
+        const METHODS: &[MethodInfo] = &[MethodInfo {
+            name: "method",
+            id: 1,
+            legacy_ids: &[2],
+            hash: 0,
+            args: &[],
+            mutable: false,
+            docs: &[],
+            signature: None,
+        }];
+
+        assert!(shares_method_id(METHODS, &[1]));
+        assert!(shares_method_id(METHODS, &[3, 2]));
+        assert!(!shares_method_id(METHODS, &[3]));
+        assert!(!shares_method_id(METHODS, &[]));
+    }
+}
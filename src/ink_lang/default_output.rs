@@ -0,0 +1,91 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use core::marker::PhantomData;
+
+use ink::prelude::vec::Vec;
+use scale::Encode;
+
+/// Encoded default outputs of chain extension methods.
+///
+/// This trait is automatically implemented with `#[obce::definition]` macro expansion.
+#[doc(hidden)]
+pub trait DefaultOutputs {
+    /// SCALE-encoded [`Default`] output of a method with the provided hash,
+    /// if the method output type implements [`Default`].
+    fn default_output(hash: u32) -> Option<Vec<u8>>;
+}
+
+#[doc(hidden)]
+pub struct DefaultOutput<T>(pub PhantomData<T>);
+
+impl<T> DefaultOutput<T>
+where
+    T: Default + Encode,
+{
+    #[inline]
+    // See `ToCriticalErr::try_to_critical_error`.
+    #[allow(dead_code)]
+    pub fn encoded_default(&self) -> Option<Vec<u8>> {
+        Some(T::default().encode())
+    }
+}
+
+#[doc(hidden)]
+pub trait DefaultOutputFallback {
+    fn encoded_default(&self) -> Option<Vec<u8>>;
+}
+
+impl<T> DefaultOutputFallback for DefaultOutput<T> {
+    #[inline]
+    fn encoded_default(&self) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! default_output {
+    ( $ty:ty $(,)? ) => {{
+        #[allow(unused_imports)]
+        use $crate::ink_lang::DefaultOutputFallback;
+        $crate::ink_lang::DefaultOutput::<$ty>(::core::marker::PhantomData).encoded_default()
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn default_output_works_if_default_implemented() {
+        assert_eq!(default_output!(u32), Some(vec![0, 0, 0, 0]));
+        assert_eq!(default_output!(Option<u8>), Some(vec![0]));
+    }
+
+    #[test]
+    fn default_output_works_if_default_is_not_implemented() {
+        struct NoDefault;
+
+        impl scale::Encode for NoDefault {}
+
+        assert_eq!(default_output!(NoDefault), None);
+        assert_eq!(default_output!(Result<u32, u8>), None);
+    }
+}
@@ -0,0 +1,1083 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use core::{
+    any::Any,
+    fmt,
+    marker::PhantomData,
+    mem,
+};
+use std::{
+    cell::{
+        Cell,
+        Ref,
+        RefCell,
+        RefMut,
+    },
+    collections::BTreeMap,
+    panic::{
+        self,
+        AssertUnwindSafe,
+    },
+    rc::Rc,
+    thread,
+};
+
+use ink::env::{
+    DefaultEnvironment,
+    Environment,
+};
+use scale::{
+    Decode,
+    Encode,
+};
+
+use crate::{
+    codegen::{
+        ErrorDescription,
+        ExtensionDescription,
+        MethodDescription,
+        MethodInfo,
+        MethodOwner,
+    },
+    ink_lang::{
+        env::test::ChainExtension,
+        DefaultOutputs,
+    },
+};
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+
+type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+/// Critical chain extension failure.
+///
+/// On-chain, critical errors abort the contract execution. Mocked methods can simulate
+/// this behaviour by returning `Result<Output, Trap>` instead of `Output`:
+///
+/// ```ignore
+/// #[obce::mock]
+/// impl MyChainExtension for () {
+///     fn method(&mut self, val: u32) -> Result<u32, obce::ink_lang::Trap> {
+///         Err(obce::ink_lang::Trap("method is not available"))
+///     }
+/// }
+/// ```
+///
+/// The returned trap unwinds the contract call, and can be caught using [`catch_trap`].
+///
+/// Unlike on-chain traps, unwinding does not revert the changes made by the contract
+/// before the chain extension call. Contract storage fields, as well as values written
+/// to the off-chain storage (for example, with `Mapping`), keep their modified state,
+/// so tests should not rely on them after a trap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trap(pub &'static str);
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chain extension trapped: {}", self.0)
+    }
+}
+
+impl std::error::Error for Trap {}
+
+/// Call the provided closure, catching [`Trap`]s raised by mocked chain extensions.
+///
+/// Panics that are not caused by a [`Trap`] are propagated further.
+///
+/// ```ignore
+/// let result = obce::ink_lang::mock::catch_trap(|| contract.call_method(100));
+/// assert_eq!(result, Err(obce::ink_lang::Trap("method is not available")));
+/// ```
+pub fn catch_trap<R, F: FnOnce() -> R>(f: F) -> Result<R, Trap> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        match payload.downcast::<Trap>() {
+            Ok(trap) => *trap,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    })
+}
+
+/// Conversion of mocked method return values into chain extension method output.
+#[doc(hidden)]
+pub trait IntoMockOutput<O> {
+    fn into_mock_output(self) -> O;
+}
+
+impl<O> IntoMockOutput<O> for O {
+    fn into_mock_output(self) -> O {
+        self
+    }
+}
+
+impl<O> IntoMockOutput<O> for Result<O, Trap> {
+    fn into_mock_output(self) -> O {
+        self.unwrap_or_else(|trap| panic::panic_any(trap))
+    }
+}
+
+/// Conversion of mocked method outputs into status codes, used by methods marked with `#[obce(ret_val)]`.
+#[doc(hidden)]
+pub trait TryIntoRetVal: Sized {
+    fn try_into_ret_val(self) -> Result<u32, Self>;
+}
+
+impl<T, E: ErrorDescription> TryIntoRetVal for Result<T, E> {
+    fn try_into_ret_val(self) -> Result<u32, Self> {
+        match self {
+            Err(error) => error.try_into_ret_val().map_err(Err),
+            ok => Err(ok),
+        }
+    }
+}
+
+/// Weight charged by mocked chain extension methods.
+///
+/// With the `substrate` feature enabled this is the Substrate `Weight` type itself,
+/// which allows `#[obce(weight(expr = ...))]` expressions to be shared between
+/// implementations and mocks.
+#[cfg(feature = "substrate")]
+pub use frame_support::dispatch::Weight;
+
+/// Weight charged by mocked chain extension methods.
+///
+/// This type mirrors the Substrate `Weight` API, which allows `#[obce(weight(expr = ...))]`
+/// expressions to be shared between implementations and mocks. Enable the `substrate` feature
+/// to use the Substrate type directly, or convert between the two with
+/// `Weight::from_parts(weight.ref_time(), weight.proof_size())`.
+#[cfg(not(feature = "substrate"))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Weight {
+    ref_time: u64,
+    proof_size: u64,
+}
+
+#[cfg(not(feature = "substrate"))]
+impl Weight {
+    /// Weight with both components set to zero.
+    pub const fn zero() -> Self {
+        Weight::from_parts(0, 0)
+    }
+
+    /// Create a weight from its components.
+    pub const fn from_parts(ref_time: u64, proof_size: u64) -> Self {
+        Weight { ref_time, proof_size }
+    }
+
+    /// Computational time component.
+    pub const fn ref_time(&self) -> u64 {
+        self.ref_time
+    }
+
+    /// Proof size component.
+    pub const fn proof_size(&self) -> u64 {
+        self.proof_size
+    }
+
+    /// Add two weights, saturating at the numeric bounds.
+    pub const fn saturating_add(self, other: Self) -> Self {
+        Weight {
+            ref_time: self.ref_time.saturating_add(other.ref_time),
+            proof_size: self.proof_size.saturating_add(other.proof_size),
+        }
+    }
+
+    /// Check if any component of `self` is greater than the corresponding component of `other`.
+    pub const fn any_gt(&self, other: Self) -> bool {
+        self.ref_time > other.ref_time || self.proof_size > other.proof_size
+    }
+}
+
+/// Trap raised when a mocked chain extension runs out of gas.
+pub const OUT_OF_GAS: Trap = Trap("OutOfGas");
+
+#[derive(Clone)]
+struct EnvSnapshot {
+    caller: Option<Rc<dyn Any>>,
+    callee: Option<Rc<dyn Any>>,
+    block_number: Rc<dyn Any>,
+    block_timestamp: Rc<dyn Any>,
+}
+
+thread_local! {
+    static ENV_SNAPSHOT: RefCell<Option<EnvSnapshot>> = const { RefCell::new(None) };
+    static CAPTURE_ACCOUNTS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// ink! test environment, as seen by the chain extension call.
+///
+/// ink! off-chain environment cannot be accessed while chain extension is being called,
+/// thus OBCE captures its state right before calling chain extension methods generated
+/// by [`obce::definition`](macro@crate::definition). You can use [`MockEnv`]
+/// inside mocked methods to access the captured state:
+///
+/// ```ignore
+/// #[obce::mock]
+/// impl Balances for State {
+///     fn balance(&self) -> u128 {
+///         self.balances[&MockEnv::caller()]
+///     }
+/// }
+/// ```
+///
+/// The state is captured using the environment of the chain extension definition
+/// (see `ink_environment` attribute of [`obce::definition`](macro@crate::definition)).
+/// Methods without the `_of` suffix return types of [`DefaultEnvironment`], while
+/// definitions with a custom environment can use the suffixed versions instead:
+///
+/// ```ignore
+/// let caller = MockEnv::caller_of::<CustomEnvironment>();
+/// ```
+///
+/// Caller and callee are captured only after [`MockEnv::capture_accounts`] is called by the test,
+/// since ink! panics when reading accounts that were not set in the test environment.
+///
+/// Capturing is enabled with the `ink-mock` feature of OBCE, which should only be
+/// activated for off-chain tests. Calls that are made without
+/// [`obce::definition`](macro@crate::definition)-generated methods see the state
+/// captured during the latest generated method call.
+pub struct MockEnv;
+
+impl MockEnv {
+    #[doc(hidden)]
+    pub fn capture<E: Environment>() {
+        // ink! panics when reading accounts that are not set, thus they are read only on request.
+        let capture_accounts = CAPTURE_ACCOUNTS.with(Cell::get);
+
+        let snapshot = EnvSnapshot {
+            caller: capture_accounts.then(|| Rc::new(ink::env::caller::<E>()) as Rc<dyn Any>),
+            callee: capture_accounts.then(|| Rc::new(ink::env::account_id::<E>()) as Rc<dyn Any>),
+            block_number: Rc::new(ink::env::block_number::<E>()),
+            block_timestamp: Rc::new(ink::env::block_timestamp::<E>()),
+        };
+
+        ENV_SNAPSHOT.with(|env| *env.borrow_mut() = Some(snapshot));
+    }
+
+    /// Capture caller and callee of the following chain extension calls on the current thread,
+    /// making them available via [`MockEnv::caller`] and [`MockEnv::callee`].
+    ///
+    /// Both accounts have to be set in the ink! test environment, for example
+    /// with `#[ink::test]` or `ink::env::test::set_caller`, since ink! panics otherwise.
+    pub fn capture_accounts() {
+        CAPTURE_ACCOUNTS.with(|capture| capture.set(true));
+    }
+
+    fn snapshot() -> EnvSnapshot {
+        ENV_SNAPSHOT
+            .with(|env| env.borrow().clone())
+            .expect("`MockEnv` is available only during chain extension calls")
+    }
+
+    fn downcast<T: Clone + 'static>(value: &Rc<dyn Any>) -> T {
+        value
+            .downcast_ref::<T>()
+            .cloned()
+            .expect("requested type differs from the one of the environment used by the chain extension definition")
+    }
+
+    /// Caller of the contract that calls the chain extension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the caller is not captured, see [`MockEnv::capture_accounts`].
+    pub fn caller() -> AccountId {
+        Self::caller_of::<DefaultEnvironment>()
+    }
+
+    /// Caller of the contract that calls the chain extension, using the provided environment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the caller is not captured (see [`MockEnv::capture_accounts`]),
+    /// or if the environment doesn't match the captured one.
+    pub fn caller_of<E: Environment>() -> E::AccountId {
+        Self::downcast(
+            &Self::snapshot()
+                .caller
+                .expect("caller is not captured, call `MockEnv::capture_accounts` first"),
+        )
+    }
+
+    /// Contract that calls the chain extension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the callee is not captured, see [`MockEnv::capture_accounts`].
+    pub fn callee() -> AccountId {
+        Self::callee_of::<DefaultEnvironment>()
+    }
+
+    /// Contract that calls the chain extension, using the provided environment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the callee is not captured (see [`MockEnv::capture_accounts`]),
+    /// or if the environment doesn't match the captured one.
+    pub fn callee_of<E: Environment>() -> E::AccountId {
+        Self::downcast(
+            &Self::snapshot()
+                .callee
+                .expect("callee is not captured, call `MockEnv::capture_accounts` first"),
+        )
+    }
+
+    /// Current block number.
+    pub fn block_number() -> BlockNumber {
+        Self::block_number_of::<DefaultEnvironment>()
+    }
+
+    /// Current block number, using the provided environment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the environment doesn't match the captured one.
+    pub fn block_number_of<E: Environment>() -> E::BlockNumber {
+        Self::downcast(&Self::snapshot().block_number)
+    }
+
+    /// Current block timestamp.
+    pub fn block_timestamp() -> Timestamp {
+        Self::block_timestamp_of::<DefaultEnvironment>()
+    }
+
+    /// Current block timestamp, using the provided environment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the environment doesn't match the captured one.
+    pub fn block_timestamp_of<E: Environment>() -> E::Timestamp {
+        Self::downcast(&Self::snapshot().block_timestamp)
+    }
+}
+
+/// Mock state, separated by the callee contract.
+///
+/// In multi-contract tests, all contracts share the same chain extension mocks.
+/// [`PerCallee`] allows to keep a separate state for each contract, while
+/// still using a single mock context:
+///
+/// ```ignore
+/// #[derive(Default)]
+/// pub struct Balances(PerCallee<u128>);
+///
+/// #[obce::mock]
+/// impl AssetsExtension for Balances {
+///     fn mint(&mut self, amount: u128) {
+///         *self.0.current() += amount;
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PerCallee<S> {
+    states: BTreeMap<AccountId, S>,
+}
+
+impl<S> PerCallee<S> {
+    /// Create an empty container.
+    pub fn new() -> Self {
+        PerCallee {
+            states: BTreeMap::new(),
+        }
+    }
+
+    /// State of the contract that is currently calling the chain extension.
+    ///
+    /// The state is created with [`Default`] value on first access.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of the chain extension call, or if the callee
+    /// is not captured, see [`MockEnv::capture_accounts`].
+    pub fn current(&mut self) -> &mut S
+    where
+        S: Default,
+    {
+        self.states.entry(MockEnv::callee()).or_default()
+    }
+
+    /// State of the provided contract.
+    pub fn get(&self, callee: &AccountId) -> Option<&S> {
+        self.states.get(callee)
+    }
+
+    /// Mutable state of the provided contract.
+    pub fn get_mut(&mut self, callee: &AccountId) -> Option<&mut S> {
+        self.states.get_mut(callee)
+    }
+
+    /// Set the state of the provided contract, returning the previous one.
+    pub fn insert(&mut self, callee: AccountId, state: S) -> Option<S> {
+        self.states.insert(callee, state)
+    }
+
+    /// Iterate over all contracts and their states.
+    pub fn iter(&self) -> impl Iterator<Item = (&AccountId, &S)> {
+        self.states.iter()
+    }
+}
+
+impl<S> Default for PerCallee<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Chain extension mock registration.
+///
+/// This trait is implemented for the mock context by [`#[obce::mock(register_fn = false)]`](macro@crate::mock),
+/// with `T` being the mocked chain extension definition (for example, `dyn MyChainExtension`).
+///
+/// Unlike the generated registration functions, [`Mock`] can be used with generic code,
+/// and allows a single context type to mock multiple chain extensions:
+///
+/// ```ignore
+/// #[obce::mock(register_fn = false)]
+/// impl RandExtension for RandContext {
+///     // ...
+/// }
+///
+/// #[obce::mock(register_fn = false)]
+/// impl BalancesExtension for BalancesContext {
+///     // ...
+/// }
+///
+/// let rand = obce::ink_lang::Mock::<dyn RandExtension>::register(RandContext::default());
+/// let (rand, balances) = obce::register_mocks!(
+///     RandContext::default() => dyn RandExtension,
+///     BalancesContext::default() => dyn BalancesExtension,
+/// );
+/// ```
+pub trait Mock<T: ?Sized>: Sized {
+    /// Register mocked chain extension methods in the off-chain environment.
+    fn register(self) -> MockHandle<T, Self>;
+}
+
+/// A single chain extension call, as received by a mocked chain extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    /// Full function identifier, containing both chain extension and method identifiers.
+    pub func_id: u32,
+
+    /// SCALE-encoded method arguments.
+    pub input: Vec<u8>,
+}
+
+impl MockCall {
+    /// Chain extension identifier.
+    pub fn ext_id(&self) -> u16 {
+        (self.func_id >> 16) as u16
+    }
+
+    /// Chain extension method identifier.
+    pub fn method_id(&self) -> u16 {
+        (self.func_id & 0xFFFF) as u16
+    }
+}
+
+type Handler = Rc<dyn Fn(u32, &[u8]) -> Vec<u8>>;
+
+/// Behaviour of chain extension methods that are not implemented by the mock.
+///
+/// Use [`MockHandle::set_fallback`] to change the fallback behaviour.
+#[derive(Clone, Default)]
+pub enum Fallback {
+    /// Panic with the method name.
+    #[default]
+    Panic,
+
+    /// Return [`Default`] value of the method output type.
+    ///
+    /// Methods with output types that do not implement [`Default`] panic instead.
+    Default,
+
+    /// Return the provided SCALE-encoded output.
+    Output(Vec<u8>),
+
+    /// Return a chain extension error, created with [`Fallback::error`] or [`Fallback::error_ret_val`].
+    Error(FallbackError),
+
+    /// Call the provided closure with the full function identifier and raw method input,
+    /// returning SCALE-encoded output.
+    Handler(Handler),
+}
+
+/// Chain extension error returned by [`Fallback::Error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FallbackError {
+    /// Status code of an error variant marked with `#[obce(ret_val = "...")]`.
+    RetVal(u32),
+
+    /// SCALE-encoded `Err` value written to the output buffer.
+    Output(Vec<u8>),
+}
+
+impl Fallback {
+    /// Return `Err(error)` from every unimplemented method.
+    ///
+    /// Methods of the mocked chain extension are expected to return `Result<_, E>`.
+    /// The error is always written to the output buffer, same as methods
+    /// without `#[obce(ret_val)]` attribute do.
+    pub fn error<E: Encode>(error: E) -> Self {
+        Fallback::Error(FallbackError::Output(Err::<(), E>(error).encode()))
+    }
+
+    /// Same as [`Fallback::error`], but error variants marked with `#[obce(ret_val = "...")]`
+    /// are returned as status codes, same as methods marked with `#[obce(ret_val)]` do.
+    pub fn error_ret_val<E: ErrorDescription + Encode>(error: E) -> Self {
+        let error = match TryIntoRetVal::try_into_ret_val(Err::<(), E>(error)) {
+            Ok(status) => FallbackError::RetVal(status),
+            Err(output) => FallbackError::Output(output.encode()),
+        };
+
+        Fallback::Error(error)
+    }
+
+    /// Call the provided closure with the full function identifier and raw method input.
+    pub fn handler<F>(handler: F) -> Self
+    where
+        F: Fn(u32, &[u8]) -> Vec<u8> + 'static,
+    {
+        Fallback::Handler(Rc::new(handler))
+    }
+}
+
+impl fmt::Debug for Fallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fallback::Panic => f.write_str("Panic"),
+            Fallback::Default => f.write_str("Default"),
+            Fallback::Output(output) => f.debug_tuple("Output").field(output).finish(),
+            Fallback::Error(error) => f.debug_tuple("Error").field(error).finish(),
+            Fallback::Handler(_) => f.write_str("Handler"),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct MockState<C> {
+    context: C,
+    fallback: Fallback,
+    fallback_weight: Weight,
+    gas_limit: Option<Weight>,
+    consumed: Weight,
+    calls: Vec<MockCall>,
+    expectations: BTreeMap<u32, Vec<Box<dyn Any>>>,
+    verifiers: Vec<Box<dyn Fn() -> Option<String>>>,
+}
+
+impl<C> MockState<C> {
+    pub fn new(context: C) -> Self {
+        MockState {
+            context,
+            fallback: Fallback::default(),
+            fallback_weight: Weight::zero(),
+            gas_limit: None,
+            consumed: Weight::zero(),
+            calls: Vec::new(),
+            expectations: BTreeMap::new(),
+            verifiers: Vec::new(),
+        }
+    }
+
+    pub fn context(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    pub fn record(&mut self, func_id: u32, input: Vec<u8>) {
+        self.calls.push(MockCall { func_id, input });
+    }
+
+    /// Charge weight from the gas budget, failing with [`OUT_OF_GAS`] if the budget is exhausted.
+    pub fn charge_weight(&mut self, weight: Weight) -> Result<(), Trap> {
+        let consumed = self.consumed.saturating_add(weight);
+
+        match self.gas_limit {
+            Some(gas_limit) if consumed.any_gt(gas_limit) => Err(OUT_OF_GAS),
+            _ => {
+                self.consumed = consumed;
+                Ok(())
+            }
+        }
+    }
+
+    /// Find the first expectation of method `hash` that matches `input`.
+    ///
+    /// The returned expectation has its call counter already incremented.
+    pub fn expectation<I: 'static, O: 'static>(&mut self, hash: u32, input: &I) -> Option<Expectation<I, O>> {
+        let matching = self
+            .expectations
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .filter_map(|expectation| expectation.downcast_ref::<Expectation<I, O>>())
+            .filter(|expectation| expectation.matches(input))
+            .collect::<Vec<_>>();
+
+        let expectation = match matching.iter().find(|expectation| !expectation.is_saturated()) {
+            Some(expectation) => expectation,
+            None if matching.is_empty() => return None,
+            None => {
+                let inner = matching[0].inner.borrow();
+                panic!(
+                    "`{}` was called more times than expected ({} times)",
+                    inner.name,
+                    inner.calls + 1
+                )
+            }
+        };
+
+        expectation.inner.borrow_mut().calls += 1;
+
+        Some(Expectation {
+            inner: expectation.inner.clone(),
+        })
+    }
+
+    fn verify(&self) -> Result<(), String> {
+        let errors = self.verifiers.iter().filter_map(|verify| verify()).collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+type Matcher<I> = Box<dyn Fn(&I) -> bool>;
+
+type Returning<I, O> = Box<dyn FnMut(I) -> O>;
+
+struct ExpectationInner<I, O> {
+    name: &'static str,
+    matcher: Option<Matcher<I>>,
+    times: Option<usize>,
+    calls: usize,
+    returning: Option<Returning<I, O>>,
+}
+
+/// Puts the `returning` closure back into the expectation when dropped.
+struct RestoreReturning<'a, I, O> {
+    inner: &'a RefCell<ExpectationInner<I, O>>,
+    returning: Option<Returning<I, O>>,
+}
+
+impl<'a, I, O> Drop for RestoreReturning<'a, I, O> {
+    fn drop(&mut self) {
+        self.inner.borrow_mut().returning = self.returning.take();
+    }
+}
+
+/// Expected call of a mocked chain extension method.
+///
+/// Expectations are created using generated `expect_*` methods of a [`MockHandle`].
+/// Every expectation may constrain the arguments it matches, the number of times
+/// it should be called, and provide a return value.
+///
+/// When a matching expectation has no return value, the mocked method body is called instead.
+pub struct Expectation<I, O> {
+    inner: Rc<RefCell<ExpectationInner<I, O>>>,
+}
+
+impl<I: 'static, O: 'static> Expectation<I, O> {
+    fn new(name: &'static str) -> Self {
+        Expectation {
+            inner: Rc::new(RefCell::new(ExpectationInner {
+                name,
+                matcher: None,
+                times: None,
+                calls: 0,
+                returning: None,
+            })),
+        }
+    }
+
+    /// Match only calls with the provided arguments.
+    ///
+    /// Methods with multiple arguments accept a tuple of arguments.
+    pub fn with(self, input: I) -> Self
+    where
+        I: PartialEq,
+    {
+        self.withf(move |value| value == &input)
+    }
+
+    /// Match only calls with arguments that satisfy the provided predicate.
+    pub fn withf<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&I) -> bool + 'static,
+    {
+        self.inner.borrow_mut().matcher = Some(Box::new(predicate));
+        self
+    }
+
+    /// Require this expectation to be called exactly `times` times.
+    pub fn times(self, times: usize) -> Self {
+        self.inner.borrow_mut().times = Some(times);
+        self
+    }
+
+    /// Require this expectation to never be called.
+    pub fn never(self) -> Self {
+        self.times(0)
+    }
+
+    /// Compute the return value using the provided closure.
+    pub fn returning<F>(self, f: F) -> Self
+    where
+        F: FnMut(I) -> O + 'static,
+    {
+        self.inner.borrow_mut().returning = Some(Box::new(f));
+        self
+    }
+
+    /// Abort the contract call with the provided [`Trap`] on every call.
+    ///
+    /// Contract state changes made before the call are not reverted, see [`Trap`].
+    pub fn trap(self, trap: Trap) -> Self {
+        self.returning(move |_| panic::panic_any(trap))
+    }
+
+    /// Return a clone of the provided value on every call.
+    pub fn return_const(self, output: O) -> Self
+    where
+        O: Clone,
+    {
+        self.returning(move |_| output.clone())
+    }
+
+    /// Number of calls matched by this expectation so far.
+    pub fn call_count(&self) -> usize {
+        self.inner.borrow().calls
+    }
+
+    #[doc(hidden)]
+    pub fn call(&self, input: I) -> Result<O, I> {
+        // The closure is taken out for the duration of the call to allow it
+        // to access the expectation itself, and is put back even if it traps.
+        let Some(returning) = self.inner.borrow_mut().returning.take() else {
+            return Err(input)
+        };
+
+        let mut guard = RestoreReturning {
+            inner: &self.inner,
+            returning: Some(returning),
+        };
+
+        let returning = guard.returning.as_mut().expect("closure is taken above");
+
+        Ok(returning(input))
+    }
+
+    fn matches(&self, input: &I) -> bool {
+        match &self.inner.borrow().matcher {
+            Some(matcher) => matcher(input),
+            None => true,
+        }
+    }
+
+    fn is_saturated(&self) -> bool {
+        let inner = self.inner.borrow();
+        matches!(inner.times, Some(times) if inner.calls >= times)
+    }
+
+    fn verify(&self) -> Option<String> {
+        let inner = self.inner.borrow();
+
+        match inner.times {
+            Some(times) if inner.calls != times => {
+                Some(format!(
+                    "`{}` was expected to be called {} times, but was called {} times",
+                    inner.name, times, inner.calls
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<I, O> Clone for Expectation<I, O> {
+    fn clone(&self) -> Self {
+        Expectation {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<I, O> fmt::Debug for Expectation<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+
+        f.debug_struct("Expectation")
+            .field("name", &inner.name)
+            .field("times", &inner.times)
+            .field("calls", &inner.calls)
+            .finish()
+    }
+}
+
+/// Handle to chain extension mocks registered with [`obce::mock`](macro@crate::mock).
+///
+/// The handle provides access to recorded calls and expectations of a mocked chain extension `T`,
+/// with `C` being the mock context.
+///
+/// Unsatisfied expectations are reported when the handle is dropped, or when
+/// [`MockHandle::checkpoint`] is called.
+pub struct MockHandle<T: ?Sized, C> {
+    state: Rc<RefCell<MockState<C>>>,
+    _ghost: PhantomData<fn() -> Box<T>>,
+}
+
+impl<T: ?Sized, C> MockHandle<T, C> {
+    #[doc(hidden)]
+    pub fn new(state: Rc<RefCell<MockState<C>>>) -> Self {
+        MockHandle {
+            state,
+            _ghost: PhantomData,
+        }
+    }
+
+    /// Immutably borrow the mock context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context is currently mutably borrowed.
+    pub fn borrow(&self) -> Ref<'_, C> {
+        Ref::map(self.state.borrow(), |state| &state.context)
+    }
+
+    /// Mutably borrow the mock context.
+    ///
+    /// The context must not be borrowed while calling the contract,
+    /// since the mocked methods borrow it as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context is currently borrowed.
+    pub fn borrow_mut(&self) -> RefMut<'_, C> {
+        RefMut::map(self.state.borrow_mut(), |state| &mut state.context)
+    }
+
+    /// Replace the mock context, returning the previous one.
+    ///
+    /// Recorded calls and expectations are left intact.
+    pub fn replace(&self, context: C) -> C {
+        mem::replace(&mut *self.borrow_mut(), context)
+    }
+
+    /// Reset the mock context to its default value, returning the previous one.
+    pub fn reset(&self) -> C
+    where
+        C: Default,
+    {
+        self.replace(C::default())
+    }
+
+    /// Set the behaviour of chain extension methods that are not implemented by the mock.
+    pub fn set_fallback(&self, fallback: Fallback) {
+        self.state.borrow_mut().fallback = fallback;
+    }
+
+    /// Set the weight charged by chain extension methods that are not implemented by the mock.
+    ///
+    /// By default, such methods do not charge any weight.
+    pub fn set_fallback_weight(&self, weight: Weight) {
+        self.state.borrow_mut().fallback_weight = weight;
+    }
+
+    /// Limit the total weight that mocked methods can charge.
+    ///
+    /// Calls that exceed the limit abort the contract call with [`OUT_OF_GAS`] trap,
+    /// same as the runtime does.
+    pub fn set_gas_limit(&self, gas_limit: Weight) {
+        self.state.borrow_mut().gas_limit = Some(gas_limit);
+    }
+
+    /// Total weight charged by mocked methods so far.
+    pub fn consumed_weight(&self) -> Weight {
+        self.state.borrow().consumed
+    }
+
+    /// All chain extension calls received by the mock so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.borrow().calls.clone()
+    }
+
+    /// Verify all expectations, and remove them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any expectation was called an unexpected number of times.
+    pub fn checkpoint(&self) {
+        let mut state = self.state.borrow_mut();
+        let result = state.verify();
+
+        state.expectations.clear();
+        state.verifiers.clear();
+
+        if let Err(error) = result {
+            panic!("{error}");
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn expectation<const HASH: u32, Path>(
+        &self,
+        name: &'static str,
+    ) -> Expectation<<T::Owner as MethodDescription<HASH>>::Input, <T::Owner as MethodDescription<HASH>>::Output>
+    where
+        T: MethodOwner<HASH, Path>,
+        <T::Owner as MethodDescription<HASH>>::Input: 'static,
+        <T::Owner as MethodDescription<HASH>>::Output: 'static,
+    {
+        let expectation = Expectation::new(name);
+        let mut state = self.state.borrow_mut();
+
+        state
+            .expectations
+            .entry(HASH)
+            .or_default()
+            .push(Box::new(expectation.clone()));
+
+        let verified = expectation.clone();
+        state.verifiers.push(Box::new(move || verified.verify()));
+
+        expectation
+    }
+
+    #[doc(hidden)]
+    pub fn recorded_inputs<const HASH: u32, Path>(&self) -> Vec<<T::Owner as MethodDescription<HASH>>::Input>
+    where
+        T: MethodOwner<HASH, Path>,
+        <T::Owner as MethodDescription<HASH>>::Input: Decode,
+    {
+        let func_id =
+            (<T::Owner as ExtensionDescription>::ID as u32) << 16 | (<T::Owner as MethodDescription<HASH>>::ID as u32);
+
+        self.state
+            .borrow()
+            .calls
+            .iter()
+            .filter(|call| call.func_id == func_id)
+            .map(|call| Decode::decode(&mut &call.input[..]).expect("recorded input is always valid"))
+            .collect()
+    }
+}
+
+/// Proxy for chain extension methods that are not implemented by the mock.
+#[doc(hidden)]
+pub struct FallbackProxy<T: ?Sized, C> {
+    ext_id: u16,
+    method: &'static MethodInfo,
+    state: Rc<RefCell<MockState<C>>>,
+    _ghost: PhantomData<fn() -> Box<T>>,
+}
+
+impl<T: ?Sized, C> FallbackProxy<T, C> {
+    pub fn new(ext_id: u16, method: &'static MethodInfo, state: Rc<RefCell<MockState<C>>>) -> Self {
+        FallbackProxy {
+            ext_id,
+            method,
+            state,
+            _ghost: PhantomData,
+        }
+    }
+}
+
+impl<T, C> ChainExtension for FallbackProxy<T, C>
+where
+    T: ?Sized + DefaultOutputs,
+{
+    fn func_id(&self) -> u32 {
+        (self.ext_id as u32) << 16 | (self.method.id as u32)
+    }
+
+    fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+        let bytes: Vec<u8> = Decode::decode(&mut &input[..]).unwrap();
+
+        let (fallback, charged) = {
+            let mut state = self.state.borrow_mut();
+            state.record(self.func_id(), bytes.clone());
+
+            // Weight is charged before the call, same as with mocked methods.
+            let weight = state.fallback_weight;
+            (state.fallback.clone(), state.charge_weight(weight))
+        };
+
+        if let Err(trap) = charged {
+            panic::panic_any(trap);
+        }
+
+        let name = self.method.name;
+
+        let encoded = match fallback {
+            Fallback::Panic => panic!("`{name}` is not implemented by the mock"),
+            Fallback::Default => {
+                <T as DefaultOutputs>::default_output(self.method.hash)
+                    .unwrap_or_else(|| panic!("output of `{name}` does not implement `Default`"))
+            }
+            Fallback::Output(encoded) => encoded,
+            Fallback::Error(FallbackError::RetVal(status)) => return status,
+            Fallback::Error(FallbackError::Output(encoded)) => encoded,
+            Fallback::Handler(handler) => handler(self.func_id(), &bytes),
+        };
+
+        output.extend(encoded);
+
+        0
+    }
+}
+
+impl<T: ?Sized, C> Drop for MockHandle<T, C> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return
+        }
+
+        if let Err(error) = self.state.borrow().verify() {
+            panic!("{error}");
+        }
+    }
+}
+
+impl<T: ?Sized, C> fmt::Debug for MockHandle<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockHandle")
+            .field("calls", &self.state.borrow().calls)
+            .finish()
+    }
+}
+
+/// Register multiple mocked chain extensions at once.
+///
+/// Accepts a list of `context => dyn Trait` pairs, and returns a tuple of the corresponding
+/// [`MockHandle`](crate::ink_lang::mock::MockHandle)s.
+///
+/// See [`Mock`] for more details.
+#[macro_export]
+macro_rules! register_mocks {
+    ($($context:expr => $extension:ty),+ $(,)?) => {
+        ($($crate::ink_lang::Mock::<$extension>::register($context),)+)
+    };
+}
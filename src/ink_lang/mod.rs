@@ -31,4 +31,40 @@ pub use ink::{
 #[cfg(feature = "std")]
 pub use ink_engine;
 
-pub use obce_macro::ink_extension as extension;
+pub use obce_macro::{
+    ink_environment as environment,
+    ink_extension as extension,
+};
+
+mod default_output;
+
+pub use default_output::{
+    DefaultOutput,
+    DefaultOutputFallback,
+    DefaultOutputs,
+};
+
+/// Capture ink! test environment for [`MockEnv`](mock::MockEnv).
+///
+/// This function is called by [`obce::definition`](macro@crate::definition)-generated
+/// methods, and does nothing unless the `ink-mock` feature is enabled.
+#[doc(hidden)]
+#[inline(always)]
+pub fn capture_env<E: env::Environment>() {
+    #[cfg(feature = "ink-mock")]
+    mock::MockEnv::capture::<E>();
+}
+
+/// Utilities for testing ink! smart contracts with mocked chain extensions.
+#[cfg(feature = "std")]
+pub mod mock;
+
+/// Replaying of recorded chain extension calls in ink! off-chain tests.
+#[cfg(feature = "std")]
+pub mod replay;
+
+#[cfg(feature = "std")]
+pub use mock::{
+    Mock,
+    Trap,
+};
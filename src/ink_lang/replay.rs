@@ -0,0 +1,202 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use std::{
+    cell::RefCell,
+    collections::{
+        BTreeSet,
+        VecDeque,
+    },
+    fmt,
+    io,
+    panic,
+    path::Path,
+    rc::Rc,
+};
+
+use scale::Decode;
+
+use crate::{
+    ink_lang::{
+        env::test::ChainExtension,
+        mock::Trap,
+    },
+    recording::{
+        self,
+        RecordedCall,
+        RecordedRetVal,
+    },
+};
+
+/// Trap raised when a replayed chain extension call stopped contract execution.
+pub const DIVERGED: Trap = Trap("Diverged");
+
+#[derive(Default)]
+struct ReplayState {
+    calls: VecDeque<RecordedCall>,
+    served: usize,
+}
+
+/// Chain extension mock that replays previously recorded calls.
+///
+/// [`Replay`] serves calls recorded with `obce::substrate::mock::Recorder` in the same order
+/// they were recorded. Every call made by the contract has to match the next recorded call exactly,
+/// including chain extension and method identifiers, as well as the raw input.
+/// Mismatched calls result in a panic describing both calls.
+///
+/// Recorded calls that stopped contract execution ([`RecordedRetVal::Diverging`]) raise
+/// a [`DIVERGED`] trap, which can be caught with [`catch_trap`](crate::ink_lang::mock::catch_trap).
+///
+/// ```ignore
+/// #[ink::test]
+/// fn replayed() {
+///     let replay = obce::ink_lang::replay::Replay::load("calls.scale").unwrap();
+///
+///     let mut contract = Contract::new();
+///     contract.method();
+///
+///     replay.assert_finished();
+/// }
+/// ```
+pub struct Replay {
+    state: Rc<RefCell<ReplayState>>,
+}
+
+impl Replay {
+    /// Register chain extension methods for every recorded call, serving the calls in order.
+    pub fn register<I: IntoIterator<Item = RecordedCall>>(calls: I) -> Self {
+        let state = Rc::new(RefCell::new(ReplayState {
+            calls: calls.into_iter().collect(),
+            served: 0,
+        }));
+
+        let func_ids = state
+            .borrow()
+            .calls
+            .iter()
+            .map(|call| (call.ext_id as u32) << 16 | (call.func_id as u32))
+            .collect::<BTreeSet<_>>();
+
+        for func_id in func_ids {
+            crate::ink_lang::env::test::register_chain_extension(ReplayProxy {
+                func_id,
+                state: state.clone(),
+            });
+        }
+
+        Replay { state }
+    }
+
+    /// Load recorded calls from a file created with [`recording::save`], and register them.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        recording::load(path).map(Self::register)
+    }
+
+    /// Number of recorded calls that were not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.state.borrow().calls.len()
+    }
+
+    /// Assert that every recorded call was replayed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any recorded calls remain.
+    pub fn assert_finished(&self) {
+        let state = self.state.borrow();
+
+        if let Some(next) = state.calls.front() {
+            panic!(
+                "{} recorded chain extension calls were not replayed, next one is {}",
+                state.calls.len(),
+                Call(next.ext_id, next.func_id, &next.input),
+            );
+        }
+    }
+}
+
+impl fmt::Debug for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replay").field("remaining", &self.remaining()).finish()
+    }
+}
+
+struct ReplayProxy {
+    func_id: u32,
+    state: Rc<RefCell<ReplayState>>,
+}
+
+impl ChainExtension for ReplayProxy {
+    fn func_id(&self) -> u32 {
+        self.func_id
+    }
+
+    fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+        let input: Vec<u8> = Decode::decode(&mut &input[..]).unwrap();
+
+        let ext_id = (self.func_id >> 16) as u16;
+        let func_id = self.func_id as u16;
+
+        let recorded = {
+            let mut state = self.state.borrow_mut();
+            let index = state.served;
+
+            let Some(recorded) = state.calls.pop_front() else {
+                panic!(
+                    "unexpected chain extension call {}: all {index} recorded calls were already replayed",
+                    Call(ext_id, func_id, &input),
+                )
+            };
+
+            if recorded.ext_id != ext_id || recorded.func_id != func_id || recorded.input != input {
+                panic!(
+                    "chain extension call #{index} does not match the recording:\n  expected: {}\n    actual: {}",
+                    Call(recorded.ext_id, recorded.func_id, &recorded.input),
+                    Call(ext_id, func_id, &input),
+                );
+            }
+
+            state.served += 1;
+            recorded
+        };
+
+        output.extend(recorded.output);
+
+        match recorded.ret_val {
+            RecordedRetVal::Converging(status) => status,
+            RecordedRetVal::Diverging { .. } => panic::panic_any(DIVERGED),
+        }
+    }
+}
+
+struct Call<'a>(u16, u16, &'a [u8]);
+
+impl<'a> fmt::Display for Call<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ext_id={}, func_id={}, input=0x", self.0, self.1)?;
+
+        for byte in self.2 {
+            write!(f, "{byte:02x}")?;
+        }
+
+        Ok(())
+    }
+}
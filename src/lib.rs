@@ -78,6 +78,16 @@
 //! and [`obce::implementation`](macro@implementation), all of which are documented
 //! in corresponding API sections.
 //!
+//! Multiple chain extensions can be registered in the runtime with `obce::substrate::extensions!`
+//! macro, which ensures that their identifiers don't collide:
+//!
+//! ```ignore
+//! impl pallet_contracts::Config for Runtime {
+//!     type ChainExtension = obce::substrate::extensions!(Runtime; MyChainExtension, AnotherChainExtension);
+//!     // ...
+//! }
+//! ```
+//!
 //! # Custom errors
 //!
 //! Your chain extension may have chain-specific errors, some of which
@@ -133,6 +143,19 @@
 //!
 //! For a complete usage example, as well as more details on how to use the macro
 //! correctly see the [corresponding API section](macro@mock).
+//!
+//! On the Substrate side, `obce::substrate::mock` module provides `MockEnvironment`,
+//! which allows you to call your chain extension implementation without a running chain,
+//! and `WeightSnapshot`, which compares charged weights with a snapshot file to
+//! catch unintended weight formula changes.
+//!
+//! Real chain extension traffic can be captured with `obce::substrate::mock::Recorder`,
+//! saved with `obce::recording::save`, and replayed in ink! off-chain tests with
+//! `obce::ink_lang::replay::Replay`, which serves recorded responses in order.
+//!
+//! With both `ink` and `substrate` features enabled, `obce::substrate::ink::InkAdapter`
+//! registers your Substrate chain extension implementation with the ink! off-chain engine,
+//! allowing contract tests to exercise production logic instead of hand-written mocks.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -148,6 +171,14 @@ pub mod substrate;
 /// about the chain extension.
 pub mod codegen;
 
+/// Portable chain extension metadata, suitable for frontends and indexers.
+#[cfg(feature = "metadata")]
+pub mod metadata;
+
+/// Recorded chain extension calls, shared between Substrate and ink! testing utilities.
+#[cfg(feature = "std")]
+pub mod recording;
+
 pub use obce_macro::{
     definition,
     error,
@@ -0,0 +1,213 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+use scale_info::{
+    PortableRegistry,
+    Registry,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::codegen::{
+    ExtensionDescription,
+    MethodTypes,
+};
+
+/// Compatibility checks between different versions of chain extension metadata.
+pub mod compat;
+
+/// Metadata format version, increased on every incompatible format change.
+pub const METADATA_VERSION: u32 = 1;
+
+/// Portable chain extension metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// Metadata format version, see [`METADATA_VERSION`].
+    pub version: u32,
+
+    /// Chain extension name.
+    pub name: String,
+
+    /// Unique chain extension identifier.
+    pub id: u16,
+
+    /// Chain extension version, see [`ExtensionDescription::VERSION`].
+    #[serde(default = "default_extension_version")]
+    pub extension_version: u16,
+
+    /// Chain extension methods, in definition order.
+    pub methods: Vec<MethodMetadata>,
+
+    /// Registry of input and output types, referenced by [`MethodMetadata`].
+    pub types: PortableRegistry,
+}
+
+/// Portable chain extension method metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodMetadata {
+    /// Method name.
+    pub name: String,
+
+    /// Unique chain extension method identifier.
+    pub id: u16,
+
+    /// Full method identifier, as passed by ink! smart contracts.
+    pub func_id: u32,
+
+    /// Previous method identifiers that are still dispatched by the chain extension.
+    #[serde(default)]
+    pub legacy_ids: Vec<u16>,
+
+    /// Argument names, excluding the receiver.
+    pub args: Vec<String>,
+
+    /// Whether the method accepts `&mut self`.
+    pub mutable: bool,
+
+    /// Documentation lines of the method.
+    pub docs: Vec<String>,
+
+    /// Normalized method signature, if the identifier is computed from it,
+    /// see [`MethodInfo::signature`](crate::codegen::MethodInfo::signature).
+    #[serde(default)]
+    pub signature: Option<String>,
+
+    /// Input type identifier in [`Metadata::types`].
+    ///
+    /// Equals to [`None`] if the input type doesn't implement `TypeInfo`.
+    pub input: Option<u32>,
+
+    /// Output type identifier in [`Metadata::types`].
+    ///
+    /// Equals to [`None`] if the output type doesn't implement `TypeInfo`.
+    pub output: Option<u32>,
+
+    /// Status codes of the method error.
+    pub ret_vals: Vec<RetValMetadata>,
+}
+
+/// Status code of an error variant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetValMetadata {
+    /// Error variant name.
+    pub variant: String,
+
+    /// Status code, returned to the contract instead of the error.
+    pub ret_val: u32,
+}
+
+fn default_extension_version() -> u16 {
+    1
+}
+
+impl Metadata {
+    /// Serialize metadata to pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("metadata is always serializable")
+    }
+
+    /// Deserialize metadata from JSON, previously created with [`Metadata::to_json`].
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Write metadata as JSON to the file at `path`.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    /// Read metadata from a JSON file at `path`.
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+
+        Self::from_json(&json).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+/// Portable chain extension metadata generation.
+///
+/// This trait is implemented for every chain extension definition
+/// with `std` feature of your crate enabled:
+///
+/// ```ignore
+/// use obce::metadata::ExtensionMetadata;
+///
+/// <dyn RandExtension as ExtensionMetadata>::metadata()
+///     .write("rand_extension.json")
+///     .unwrap();
+/// ```
+pub trait ExtensionMetadata {
+    /// Generate chain extension metadata.
+    fn metadata() -> Metadata;
+}
+
+impl<T> ExtensionMetadata for T
+where
+    T: ?Sized + ExtensionDescription + MethodTypes,
+{
+    fn metadata() -> Metadata {
+        let mut registry = Registry::new();
+
+        let methods = T::METHODS
+            .iter()
+            .map(|method| {
+                MethodMetadata {
+                    name: method.name.to_string(),
+                    id: method.id,
+                    func_id: (T::ID as u32) << 16 | (method.id as u32),
+                    legacy_ids: method.legacy_ids.to_vec(),
+                    args: method.args.iter().map(ToString::to_string).collect(),
+                    mutable: method.mutable,
+                    docs: method.docs.iter().map(ToString::to_string).collect(),
+                    signature: method.signature.map(ToString::to_string),
+                    input: T::input_type(method.hash).map(|ty| registry.register_type(&ty).id),
+                    output: T::output_type(method.hash).map(|ty| registry.register_type(&ty).id),
+                    ret_vals: T::ret_vals(method.hash)
+                        .iter()
+                        .map(|info| {
+                            RetValMetadata {
+                                variant: info.variant.to_string(),
+                                ret_val: info.ret_val,
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Metadata {
+            version: METADATA_VERSION,
+            name: T::NAME.to_string(),
+            id: T::ID,
+            extension_version: T::VERSION,
+            methods,
+            types: registry.into(),
+        }
+    }
+}
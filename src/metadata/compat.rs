@@ -0,0 +1,863 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use std::{
+    cmp::Reverse,
+    collections::BTreeSet,
+};
+
+use scale_info::{
+    form::PortableForm,
+    Field,
+    PortableRegistry,
+    TypeDef,
+};
+
+use super::{
+    Metadata,
+    MethodMetadata,
+};
+
+/// Kind of a change between two metadata versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    /// Change that doesn't affect deployed contracts.
+    Compatible,
+
+    /// Changed status codes of method errors.
+    ErrorCodes,
+
+    /// Change that breaks deployed contracts calling the affected identifiers.
+    Breaking,
+}
+
+/// A single change between two metadata versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Change kind.
+    pub kind: ChangeKind,
+
+    /// Name of the changed method, if the change is specific to a method.
+    pub method: Option<String>,
+
+    /// Full method identifiers (as passed by ink! smart contracts) affected by the change.
+    pub func_ids: Vec<u32>,
+
+    /// What exactly was changed, with previous and new values.
+    pub detail: ChangeDetail,
+}
+
+/// Changed part of the chain extension, with previous and new values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeDetail {
+    /// Chain extension identifier.
+    ExtensionId { old: u16, new: u16 },
+
+    /// Chain extension version.
+    ExtensionVersion { old: u16, new: u16 },
+
+    /// Method was added.
+    MethodAdded,
+
+    /// Method was renamed, keeping its identifier.
+    MethodRenamed { old: String, new: String },
+
+    /// Method was removed.
+    ///
+    /// `legacy_by` contains the name of a method that dispatches the previous identifier as legacy.
+    MethodRemoved { legacy_by: Option<String> },
+
+    /// Method identifier.
+    ///
+    /// `legacy` is `true` if the previous identifier is dispatched as legacy.
+    MethodId { old: u16, new: u16, legacy: bool },
+
+    /// Method signature, that the method identifier is computed from.
+    Signature { old: Option<String>, new: Option<String> },
+
+    /// Method input type.
+    ///
+    /// Type names are [`None`] if the type doesn't implement `TypeInfo`.
+    InputType { old: Option<String>, new: Option<String> },
+
+    /// Method output type.
+    ///
+    /// Type names are [`None`] if the type doesn't implement `TypeInfo`.
+    OutputType { old: Option<String>, new: Option<String> },
+
+    /// Whether the method accepts `&mut self`.
+    Mutability { old: bool, new: bool },
+
+    /// Argument names.
+    Args { old: Vec<String>, new: Vec<String> },
+
+    /// Status code of an error variant, [`None`] if the variant has no status code.
+    StatusCode {
+        variant: String,
+        old: Option<u32>,
+        new: Option<u32>,
+    },
+}
+
+/// Result of a comparison of two metadata versions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Every detected change.
+    pub changes: Vec<Change>,
+
+    /// Full method identifiers present in the new metadata.
+    pub new_func_ids: BTreeSet<u32>,
+
+    /// Whether the chain extension version was increased.
+    pub version_bumped: bool,
+}
+
+impl Report {
+    /// Returns `true` if any change breaks deployed contracts.
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.kind == ChangeKind::Breaking)
+    }
+
+    /// Changes of the provided kind.
+    pub fn of_kind(&self, kind: ChangeKind) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(move |change| change.kind == kind)
+    }
+
+    /// Changes that deployed contracts can't cope with.
+    ///
+    /// Breaking changes that remove affected identifiers from the new metadata (removed methods,
+    /// changed method or chain extension identifiers) are always returned, since contracts
+    /// calling such identifiers will fail regardless of the chain extension version.
+    ///
+    /// Breaking and error code changes that keep affected identifiers in the new metadata
+    /// (changed argument, return or status code types) are returned unless the chain extension
+    /// version was increased, since contracts calling such identifiers will continue to work
+    /// with a changed behaviour instead of failing.
+    pub fn unversioned(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|change| {
+            let kept = change.func_ids.iter().any(|id| self.new_func_ids.contains(id));
+
+            match change.kind {
+                ChangeKind::Compatible => false,
+                ChangeKind::Breaking if !kept => true,
+                _ => kept && !self.version_bumped,
+            }
+        })
+    }
+}
+
+/// Compare two metadata versions of the same chain extension.
+pub fn compare(old: &Metadata, new: &Metadata) -> Report {
+    let mut changes = Vec::new();
+
+    if old.id != new.id {
+        changes.push(Change {
+            kind: ChangeKind::Breaking,
+            method: None,
+            func_ids: old.methods.iter().map(|method| method.func_id).collect(),
+            detail: ChangeDetail::ExtensionId {
+                old: old.id,
+                new: new.id,
+            },
+        });
+    }
+
+    if old.extension_version != new.extension_version {
+        changes.push(Change {
+            kind: ChangeKind::Compatible,
+            method: None,
+            func_ids: vec![],
+            detail: ChangeDetail::ExtensionVersion {
+                old: old.extension_version,
+                new: new.extension_version,
+            },
+        });
+    }
+
+    for old_method in &old.methods {
+        let Some(new_method) = new
+            .methods
+            .iter()
+            .find(|method| method.name == old_method.name)
+            .or_else(|| renamed_to(old, old_method, new))
+        else {
+            let legacy_by = new
+                .methods
+                .iter()
+                .find(|method| serves_as_legacy(old, old_method, new, method))
+                .map(|method| method.name.clone());
+
+            changes.push(Change {
+                kind: if legacy_by.is_some() {
+                    ChangeKind::Compatible
+                } else {
+                    ChangeKind::Breaking
+                },
+                method: Some(old_method.name.clone()),
+                func_ids: vec![old_method.func_id],
+                detail: ChangeDetail::MethodRemoved { legacy_by },
+            });
+            continue
+        };
+
+        if new_method.name != old_method.name {
+            changes.push(Change {
+                kind: ChangeKind::Compatible,
+                method: Some(old_method.name.clone()),
+                func_ids: vec![old_method.func_id],
+                detail: ChangeDetail::MethodRenamed {
+                    old: old_method.name.clone(),
+                    new: new_method.name.clone(),
+                },
+            });
+        }
+
+        compare_method(old, old_method, new, new_method, &mut changes);
+    }
+
+    for new_method in &new.methods {
+        let existing = old.methods.iter().any(|method| {
+            method.name == new_method.name
+                || matches!(renamed_to(old, method, new), Some(renamed) if renamed.name == new_method.name)
+        });
+
+        if !existing {
+            changes.push(Change {
+                kind: ChangeKind::Compatible,
+                method: Some(new_method.name.clone()),
+                func_ids: vec![new_method.func_id],
+                detail: ChangeDetail::MethodAdded,
+            });
+        }
+    }
+
+    changes.sort_by_key(|change| Reverse(change.kind));
+
+    Report {
+        changes,
+        new_func_ids: new.methods.iter().map(|method| method.func_id).collect(),
+        version_bumped: new.extension_version > old.extension_version,
+    }
+}
+
+/// Assert that `new` metadata has no incompatible changes compared to `old` metadata.
+///
+/// Argument, return and status code type changes are allowed if the chain extension version was bumped,
+/// while removed methods and identifiers have to be dispatched as legacy instead.
+///
+/// # Panics
+///
+/// Panics with a report if [`Report::unversioned`] returns any changes.
+pub fn assert_compatible(old: &Metadata, new: &Metadata) {
+    let report = compare(old, new);
+
+    let unversioned = report.unversioned().collect::<Vec<_>>();
+
+    if !unversioned.is_empty() {
+        panic!(
+            "`{}` chain extension has incompatible changes: {unversioned:?}",
+            new.name
+        );
+    }
+}
+
+fn compare_method(
+    old: &Metadata,
+    old_method: &MethodMetadata,
+    new: &Metadata,
+    new_method: &MethodMetadata,
+    changes: &mut Vec<Change>,
+) {
+    let mut push = |kind, detail| {
+        changes.push(Change {
+            kind,
+            method: Some(old_method.name.clone()),
+            func_ids: vec![old_method.func_id],
+            detail,
+        })
+    };
+
+    if old_method.signature != new_method.signature {
+        push(
+            ChangeKind::Compatible,
+            ChangeDetail::Signature {
+                old: old_method.signature.clone(),
+                new: new_method.signature.clone(),
+            },
+        );
+    }
+
+    if serves_as_legacy(old, old_method, new, new_method) {
+        // The previous encoding is handled by the implementation,
+        // usually with an adapter method, which is not a part of the metadata.
+        push(
+            ChangeKind::Compatible,
+            ChangeDetail::MethodId {
+                old: old_method.id,
+                new: new_method.id,
+                legacy: true,
+            },
+        );
+        return
+    }
+
+    if old_method.id != new_method.id {
+        push(
+            ChangeKind::Breaking,
+            ChangeDetail::MethodId {
+                old: old_method.id,
+                new: new_method.id,
+                legacy: false,
+            },
+        );
+    }
+
+    if !same_type(old, old_method.input, new, new_method.input) {
+        push(
+            ChangeKind::Breaking,
+            ChangeDetail::InputType {
+                old: type_name(&old.types, old_method.input),
+                new: type_name(&new.types, new_method.input),
+            },
+        );
+    }
+
+    if !same_type(old, old_method.output, new, new_method.output) {
+        push(
+            ChangeKind::Breaking,
+            ChangeDetail::OutputType {
+                old: type_name(&old.types, old_method.output),
+                new: type_name(&new.types, new_method.output),
+            },
+        );
+    }
+
+    if old_method.mutable != new_method.mutable {
+        push(
+            ChangeKind::Compatible,
+            ChangeDetail::Mutability {
+                old: old_method.mutable,
+                new: new_method.mutable,
+            },
+        );
+    }
+
+    if old_method.args != new_method.args && old_method.args.len() == new_method.args.len() {
+        push(
+            ChangeKind::Compatible,
+            ChangeDetail::Args {
+                old: old_method.args.clone(),
+                new: new_method.args.clone(),
+            },
+        );
+    }
+
+    for old_ret_val in &old_method.ret_vals {
+        match new_method
+            .ret_vals
+            .iter()
+            .find(|ret_val| ret_val.variant == old_ret_val.variant)
+        {
+            Some(new_ret_val) if new_ret_val.ret_val != old_ret_val.ret_val => {
+                push(
+                    ChangeKind::ErrorCodes,
+                    ChangeDetail::StatusCode {
+                        variant: old_ret_val.variant.clone(),
+                        old: Some(old_ret_val.ret_val),
+                        new: Some(new_ret_val.ret_val),
+                    },
+                )
+            }
+            Some(_) => {}
+            None => {
+                push(
+                    ChangeKind::ErrorCodes,
+                    ChangeDetail::StatusCode {
+                        variant: old_ret_val.variant.clone(),
+                        old: Some(old_ret_val.ret_val),
+                        new: None,
+                    },
+                )
+            }
+        }
+    }
+
+    for new_ret_val in &new_method.ret_vals {
+        if !old_method
+            .ret_vals
+            .iter()
+            .any(|ret_val| ret_val.variant == new_ret_val.variant)
+        {
+            push(
+                ChangeKind::ErrorCodes,
+                ChangeDetail::StatusCode {
+                    variant: new_ret_val.variant.clone(),
+                    old: None,
+                    new: Some(new_ret_val.ret_val),
+                },
+            )
+        }
+    }
+}
+
+/// Method of `new` metadata that `old_method` was renamed to.
+///
+/// Methods without a counterpart with the same name are paired by identifiers,
+/// since renamed methods usually keep their identifiers set explicitly.
+fn renamed_to<'a>(old: &Metadata, old_method: &MethodMetadata, new: &'a Metadata) -> Option<&'a MethodMetadata> {
+    if new.methods.iter().any(|method| method.name == old_method.name) {
+        return None
+    }
+
+    new.methods
+        .iter()
+        .find(|method| method.func_id == old_method.func_id && !old.methods.iter().any(|old| old.name == method.name))
+}
+
+fn serves_as_legacy(old: &Metadata, old_method: &MethodMetadata, new: &Metadata, new_method: &MethodMetadata) -> bool {
+    old.id == new.id && old_method.id != new_method.id && new_method.legacy_ids.contains(&old_method.id)
+}
+
+fn same_type(old: &Metadata, old_ty: Option<u32>, new: &Metadata, new_ty: Option<u32>) -> bool {
+    match (old_ty, new_ty) {
+        (Some(old_ty), Some(new_ty)) => {
+            TypeComparator {
+                old: &old.types,
+                new: &new.types,
+                assumed: BTreeSet::new(),
+            }
+            .same(old_ty, new_ty)
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Human-readable name of a type from the registry, which is used only to describe changes.
+fn type_name(registry: &PortableRegistry, ty: Option<u32>) -> Option<String> {
+    let ty = registry.resolve(ty?)?;
+
+    if !ty.path.segments.is_empty() {
+        return Some(ty.path.segments.join("::"))
+    }
+
+    let name = match &ty.type_def {
+        TypeDef::Sequence(sequence) => format!("[{}]", type_name(registry, Some(sequence.type_param.id))?),
+        TypeDef::Array(array) => format!("[{}; {}]", type_name(registry, Some(array.type_param.id))?, array.len),
+        TypeDef::Tuple(tuple) => {
+            let fields = tuple
+                .fields
+                .iter()
+                .map(|field| type_name(registry, Some(field.id)))
+                .collect::<Option<Vec<_>>>()?;
+
+            format!("({})", fields.join(", "))
+        }
+        TypeDef::Primitive(primitive) => format!("{primitive:?}").to_lowercase(),
+        TypeDef::Compact(compact) => format!("Compact<{}>", type_name(registry, Some(compact.type_param.id))?),
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Structural comparison of SCALE-encoded representations of types from two registries.
+///
+/// Type and field names are ignored, since they don't affect the encoding.
+struct TypeComparator<'a> {
+    old: &'a PortableRegistry,
+    new: &'a PortableRegistry,
+    // Pairs that are assumed to be equal, which allows comparing recursive types.
+    assumed: BTreeSet<(u32, u32)>,
+}
+
+impl<'a> TypeComparator<'a> {
+    fn same(&mut self, old_id: u32, new_id: u32) -> bool {
+        if !self.assumed.insert((old_id, new_id)) {
+            return true
+        }
+
+        let (Some(old_ty), Some(new_ty)) = (self.old.resolve(old_id), self.new.resolve(new_id)) else {
+            return false
+        };
+
+        match (&old_ty.type_def, &new_ty.type_def) {
+            (TypeDef::Composite(old), TypeDef::Composite(new)) => self.same_fields(&old.fields, &new.fields),
+            (TypeDef::Variant(old), TypeDef::Variant(new)) => {
+                old.variants.len() == new.variants.len()
+                    && old.variants.iter().all(|old_variant| {
+                        new.variants.iter().any(|new_variant| {
+                            new_variant.index == old_variant.index
+                                && self.same_fields(&old_variant.fields, &new_variant.fields)
+                        })
+                    })
+            }
+            (TypeDef::Sequence(old), TypeDef::Sequence(new)) => self.same(old.type_param.id, new.type_param.id),
+            (TypeDef::Array(old), TypeDef::Array(new)) => {
+                old.len == new.len && self.same(old.type_param.id, new.type_param.id)
+            }
+            (TypeDef::Tuple(old), TypeDef::Tuple(new)) => {
+                old.fields.len() == new.fields.len()
+                    && old
+                        .fields
+                        .iter()
+                        .zip(&new.fields)
+                        .all(|(old, new)| self.same(old.id, new.id))
+            }
+            (TypeDef::Primitive(old), TypeDef::Primitive(new)) => old == new,
+            (TypeDef::Compact(old), TypeDef::Compact(new)) => self.same(old.type_param.id, new.type_param.id),
+            (TypeDef::BitSequence(old), TypeDef::BitSequence(new)) => {
+                self.same(old.bit_store_type.id, new.bit_store_type.id)
+                    && self.same(old.bit_order_type.id, new.bit_order_type.id)
+            }
+            _ => false,
+        }
+    }
+
+    fn same_fields(&mut self, old: &[Field<PortableForm>], new: &[Field<PortableForm>]) -> bool {
+        old.len() == new.len() && old.iter().zip(new).all(|(old, new)| self.same(old.ty.id, new.ty.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scale_info::{
+        MetaType,
+        Registry,
+    };
+
+    use super::{
+        assert_compatible,
+        compare,
+        Change,
+        ChangeDetail,
+        ChangeKind,
+    };
+    use crate::metadata::{
+        Metadata,
+        MethodMetadata,
+        RetValMetadata,
+        METADATA_VERSION,
+    };
+
+    struct Method {
+        name: &'static str,
+        id: u16,
+        legacy_ids: &'static [u16],
+        input: MetaType,
+        ret_vals: &'static [(&'static str, u32)],
+    }
+
+    fn metadata(id: u16, methods: &[Method]) -> Metadata {
+        let mut registry = Registry::new();
+
+        let methods = methods
+            .iter()
+            .map(|method| {
+                MethodMetadata {
+                    name: method.name.to_string(),
+                    id: method.id,
+                    func_id: (id as u32) << 16 | (method.id as u32),
+                    legacy_ids: method.legacy_ids.to_vec(),
+                    args: vec![],
+                    mutable: true,
+                    docs: vec![],
+                    signature: None,
+                    input: Some(registry.register_type(&method.input).id),
+                    output: Some(registry.register_type(&MetaType::new::<u32>()).id),
+                    ret_vals: method
+                        .ret_vals
+                        .iter()
+                        .map(|(variant, ret_val)| {
+                            RetValMetadata {
+                                variant: variant.to_string(),
+                                ret_val: *ret_val,
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Metadata {
+            version: METADATA_VERSION,
+            name: "Extension".to_string(),
+            id,
+            extension_version: 1,
+            methods,
+            types: registry.into(),
+        }
+    }
+
+    fn method(name: &'static str, id: u16, input: MetaType) -> Method {
+        Method {
+            name,
+            id,
+            legacy_ids: &[],
+            input,
+            ret_vals: &[],
+        }
+    }
+
+    #[test]
+    fn identical_metadata() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<(u32, bool)>())]);
+
+        // Type identifiers differ between registries.
+        let new = metadata(
+            1,
+            &[
+                method("second", 2, MetaType::new::<u8>()),
+                method("first", 1, MetaType::new::<(u32, bool)>()),
+            ],
+        );
+
+        let report = compare(&old, &new);
+
+        assert!(!report.is_breaking());
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.changes[0].kind, ChangeKind::Compatible);
+        assert_compatible(&old, &new);
+    }
+
+    #[test]
+    fn changed_argument_type() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = metadata(1, &[method("first", 1, MetaType::new::<u64>())]);
+
+        let report = compare(&old, &new);
+
+        assert!(report.is_breaking());
+        assert_eq!(report.changes[0].func_ids, vec![1 << 16 | 1]);
+        assert_eq!(
+            report.changes[0].detail,
+            ChangeDetail::InputType {
+                old: Some("u32".to_string()),
+                new: Some("u64".to_string()),
+            }
+        );
+        assert_eq!(report.unversioned().count(), 1);
+    }
+
+    #[test]
+    fn changed_signature() {
+        let mut old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let mut new = metadata(1, &[method("first", 2, MetaType::new::<u64>())]);
+
+        old.methods[0].signature = Some("first(u32)->u32".to_string());
+        new.methods[0].signature = Some("first(u64)->u32".to_string());
+
+        let report = compare(&old, &new);
+
+        assert!(report.changes.contains(&Change {
+            kind: ChangeKind::Compatible,
+            method: Some("first".to_string()),
+            func_ids: vec![1 << 16 | 1],
+            detail: ChangeDetail::Signature {
+                old: Some("first(u32)->u32".to_string()),
+                new: Some("first(u64)->u32".to_string()),
+            },
+        }));
+    }
+
+    #[test]
+    fn changed_argument_type_with_id_bump() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = metadata(1, &[method("first", 2, MetaType::new::<u64>())]);
+
+        let report = compare(&old, &new);
+
+        assert!(report.is_breaking());
+        assert_eq!(report.unversioned().count(), 2);
+    }
+
+    #[test]
+    fn removed_method() {
+        let old = metadata(
+            1,
+            &[
+                method("first", 1, MetaType::new::<u32>()),
+                method("second", 2, MetaType::new::<u32>()),
+            ],
+        );
+        let new = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+
+        let report = compare(&old, &new);
+
+        assert_eq!(report.of_kind(ChangeKind::Breaking).count(), 1);
+        assert_eq!(report.changes[0].method.as_deref(), Some("second"));
+        assert_eq!(report.unversioned().count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "method: Some(\"second\"), func_ids: [65538], detail: MethodRemoved { legacy_by: None }")]
+    fn removed_method_with_version_bump() {
+        let old = metadata(
+            1,
+            &[
+                method("first", 1, MetaType::new::<u32>()),
+                method("second", 2, MetaType::new::<u32>()),
+            ],
+        );
+        let new = Metadata {
+            extension_version: 2,
+            ..metadata(1, &[method("first", 1, MetaType::new::<u32>())])
+        };
+
+        let report = compare(&old, &new);
+
+        assert!(report.version_bumped);
+        assert_eq!(report.unversioned().count(), 1);
+
+        assert_compatible(&old, &new);
+    }
+
+    #[test]
+    fn changed_extension_id() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = Metadata {
+            extension_version: 2,
+            ..metadata(2, &[method("first", 1, MetaType::new::<u32>())])
+        };
+
+        let report = compare(&old, &new);
+
+        assert!(report.is_breaking());
+        assert_eq!(report.unversioned().count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "StatusCode { variant: \"Error\", old: Some(1), new: Some(2) }")]
+    fn changed_error_codes() {
+        let old = metadata(
+            1,
+            &[Method {
+                ret_vals: &[("Error", 1)],
+                ..method("first", 1, MetaType::new::<u32>())
+            }],
+        );
+        let new = metadata(
+            1,
+            &[Method {
+                ret_vals: &[("Error", 2)],
+                ..method("first", 1, MetaType::new::<u32>())
+            }],
+        );
+
+        let report = compare(&old, &new);
+
+        assert!(!report.is_breaking());
+        assert_eq!(report.of_kind(ChangeKind::ErrorCodes).count(), 1);
+
+        assert_compatible(&old, &new);
+    }
+
+    #[test]
+    fn changed_argument_type_with_version_bump() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = Metadata {
+            extension_version: 2,
+            ..metadata(1, &[method("first", 1, MetaType::new::<u64>())])
+        };
+
+        let report = compare(&old, &new);
+
+        assert!(report.is_breaking());
+        assert!(report.version_bumped);
+        assert_compatible(&old, &new);
+    }
+
+    #[test]
+    fn changed_argument_type_with_legacy_id() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = metadata(
+            1,
+            &[Method {
+                legacy_ids: &[1],
+                ..method("first", 2, MetaType::new::<u64>())
+            }],
+        );
+
+        let report = compare(&old, &new);
+
+        assert!(!report.is_breaking());
+        assert_eq!(
+            report.changes[0].detail,
+            ChangeDetail::MethodId {
+                old: 1,
+                new: 2,
+                legacy: true
+            }
+        );
+    }
+
+    #[test]
+    fn renamed_method_with_legacy_id() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = metadata(
+            1,
+            &[Method {
+                legacy_ids: &[1],
+                ..method("second", 2, MetaType::new::<u32>())
+            }],
+        );
+
+        let report = compare(&old, &new);
+
+        assert!(!report.is_breaking());
+        assert_eq!(report.of_kind(ChangeKind::Compatible).count(), 2);
+    }
+
+    #[test]
+    fn renamed_method_with_same_id() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = metadata(1, &[method("second", 1, MetaType::new::<u32>())]);
+
+        let report = compare(&old, &new);
+
+        assert!(!report.is_breaking());
+        assert_eq!(
+            report.changes,
+            vec![Change {
+                kind: ChangeKind::Compatible,
+                method: Some("first".to_string()),
+                func_ids: vec![1 << 16 | 1],
+                detail: ChangeDetail::MethodRenamed {
+                    old: "first".to_string(),
+                    new: "second".to_string(),
+                },
+            }]
+        );
+        assert_compatible(&old, &new);
+    }
+
+    #[test]
+    fn renamed_method_with_changed_argument_type() {
+        let old = metadata(1, &[method("first", 1, MetaType::new::<u32>())]);
+        let new = metadata(1, &[method("second", 1, MetaType::new::<u64>())]);
+
+        let report = compare(&old, &new);
+
+        assert!(report.is_breaking());
+        assert_eq!(report.of_kind(ChangeKind::Compatible).count(), 1);
+        assert_eq!(report.unversioned().count(), 1);
+    }
+}
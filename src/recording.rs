@@ -0,0 +1,123 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use std::{
+    fs,
+    io,
+    path::Path,
+};
+
+use scale::{
+    Decode,
+    Encode,
+};
+
+/// Result of a recorded chain extension call.
+///
+/// This type mirrors `RetVal` returned by Substrate chain extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum RecordedRetVal {
+    /// Contract execution continued with the provided status code.
+    Converging(u32),
+
+    /// Contract execution was stopped with the provided return flags and data.
+    Diverging {
+        /// Raw `ReturnFlags` bits.
+        flags: u32,
+
+        /// Data returned to the caller of the contract.
+        data: Vec<u8>,
+    },
+}
+
+/// A single chain extension call, recorded from a real runtime.
+///
+/// Recorded calls are produced by `obce::substrate::mock::Recorder`, and can be
+/// replayed in ink! off-chain tests using `obce::ink_lang::replay::Replay`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct RecordedCall {
+    /// Chain extension identifier.
+    pub ext_id: u16,
+
+    /// Chain extension method identifier.
+    pub func_id: u16,
+
+    /// Raw input passed by the contract.
+    pub input: Vec<u8>,
+
+    /// Raw output written by the chain extension.
+    pub output: Vec<u8>,
+
+    /// Call result.
+    pub ret_val: RecordedRetVal,
+}
+
+/// Save recorded calls to a file using SCALE encoding.
+pub fn save<P: AsRef<Path>>(path: P, calls: &[RecordedCall]) -> io::Result<()> {
+    fs::write(path, calls.encode())
+}
+
+/// Load recorded calls from a file, previously created with [`save`].
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<RecordedCall>> {
+    let bytes = fs::read(path)?;
+
+    Decode::decode(&mut &bytes[..]).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        load,
+        save,
+        RecordedCall,
+        RecordedRetVal,
+    };
+
+    #[test]
+    fn save_and_load() {
+        let calls = vec![
+            RecordedCall {
+                ext_id: 1,
+                func_id: 2,
+                input: vec![1, 2, 3],
+                output: vec![4, 5],
+                ret_val: RecordedRetVal::Converging(0),
+            },
+            RecordedCall {
+                ext_id: 1,
+                func_id: 3,
+                input: vec![],
+                output: vec![],
+                ret_val: RecordedRetVal::Diverging {
+                    flags: 1,
+                    data: vec![6],
+                },
+            },
+        ];
+
+        let path = std::env::temp_dir().join(format!("obce-recording-{}", std::process::id()));
+
+        save(&path, &calls).unwrap();
+        assert_eq!(load(&path).unwrap(), calls);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -152,3 +152,54 @@ where
         Environment::ext(self)
     }
 }
+
+impl<E, T, Env> ChainExtensionEnvironment<E, T> for &mut Env
+where
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    type ChargedAmount = Env::ChargedAmount;
+
+    fn func_id(&self) -> u16 {
+        Env::func_id(self)
+    }
+
+    fn ext_id(&self) -> u16 {
+        Env::ext_id(self)
+    }
+
+    fn in_len(&self) -> u32 {
+        Env::in_len(self)
+    }
+
+    fn read(&self, max_len: u32) -> Result<Vec<u8>> {
+        Env::read(self, max_len)
+    }
+
+    fn read_into(&self, buffer: &mut &mut [u8]) -> Result<()> {
+        Env::read_into(self, buffer)
+    }
+
+    fn read_as<U: Decode + MaxEncodedLen>(&mut self) -> Result<U> {
+        Env::read_as(self)
+    }
+
+    fn read_as_unbounded<U: Decode>(&mut self, len: u32) -> Result<U> {
+        Env::read_as_unbounded(self, len)
+    }
+
+    fn write(&mut self, buffer: &[u8], allow_skip: bool, weight_per_byte: Option<Weight>) -> Result<()> {
+        Env::write(self, buffer, allow_skip, weight_per_byte)
+    }
+
+    fn charge_weight(&mut self, amount: Weight) -> Result<Self::ChargedAmount> {
+        Env::charge_weight(self, amount)
+    }
+
+    fn adjust_weight(&mut self, charged: Self::ChargedAmount, actual_weight: Weight) {
+        Env::adjust_weight(self, charged, actual_weight)
+    }
+
+    fn ext(&mut self) -> &mut E {
+        Env::ext(self)
+    }
+}
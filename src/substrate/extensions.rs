@@ -0,0 +1,232 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use core::any::type_name;
+
+use pallet_contracts::{
+    chain_extension::{
+        ChainExtension,
+        Environment,
+        Ext,
+        InitState,
+        RegisteredChainExtension,
+        Result,
+        RetVal,
+    },
+    Config,
+};
+use sp_std::vec::Vec;
+
+/// Set of chain extensions with unique identifiers.
+///
+/// Use [`extensions!`](crate::substrate::extensions) macro to create this type:
+///
+/// ```ignore
+/// impl pallet_contracts::Config for Runtime {
+///     type ChainExtension = obce::substrate::extensions!(Runtime; RandExtension, AssetsExtension);
+///     // ...
+/// }
+/// ```
+///
+/// Calls are dispatched in the same way as with a plain tuple of chain extensions,
+/// but the runtime fails to compile if any two chain extensions have the same identifier.
+///
+/// Identifiers of chain extensions listed in the macro are checked during type checking,
+/// while identifiers of [`Extensions`] created without the macro are only checked
+/// when its [`ChainExtension`] implementation is instantiated, which `cargo check` doesn't report.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Extensions<L>(pub L);
+
+impl<L> Extensions<L> {
+    /// Identifiers and type names of chain extensions, in declaration order.
+    pub fn table<C>() -> Vec<(u16, &'static str)>
+    where
+        C: Config,
+        L: ExtensionSet<C>,
+    {
+        L::table()
+    }
+
+    /// Type name of a chain extension with the provided identifier.
+    pub fn lookup<C>(id: u16) -> Option<&'static str>
+    where
+        C: Config,
+        L: ExtensionSet<C>,
+    {
+        L::table()
+            .into_iter()
+            .find_map(|(ext_id, name)| (ext_id == id).then_some(name))
+    }
+}
+
+impl<C, L> ChainExtension<C> for Extensions<L>
+where
+    C: Config,
+    L: ChainExtension<C> + ExtensionSet<C>,
+{
+    fn call<E: Ext<T = C>>(&mut self, env: Environment<E, InitState>) -> Result<RetVal> {
+        #[allow(clippy::let_unit_value)]
+        let () = <L as ExtensionSet<C>>::UNIQUE_IDS;
+
+        self.0.call(env)
+    }
+
+    fn enabled() -> bool {
+        #[allow(clippy::let_unit_value)]
+        let () = <L as ExtensionSet<C>>::UNIQUE_IDS;
+
+        L::enabled()
+    }
+}
+
+/// Tuple of registered chain extensions.
+///
+/// This trait is implemented for tuples of up to 10 chain extensions,
+/// which is the limit of `pallet_contracts`.
+pub trait ExtensionSet<C: Config> {
+    /// Identifiers of chain extensions, in declaration order.
+    const IDS: &'static [u16];
+
+    /// Evaluation of this constant fails if chain extension identifiers are not unique.
+    const UNIQUE_IDS: () = assert!(
+        duplicated_id(Self::IDS).is_none(),
+        "chain extensions have duplicated identifiers, use `obce::substrate::Extensions::table` to find them"
+    );
+
+    /// Identifiers and type names of chain extensions, in declaration order.
+    fn table() -> Vec<(u16, &'static str)>;
+}
+
+macro_rules! impl_extension_set {
+    ($($extension:ident),+) => {
+        impl<C, $($extension),+> ExtensionSet<C> for ($($extension,)+)
+        where
+            C: Config,
+            $($extension: RegisteredChainExtension<C>,)+
+        {
+            const IDS: &'static [u16] = &[$(<$extension as RegisteredChainExtension<C>>::ID),+];
+
+            fn table() -> Vec<(u16, &'static str)> {
+                sp_std::vec![$((<$extension as RegisteredChainExtension<C>>::ID, type_name::<$extension>())),+]
+            }
+        }
+    };
+}
+
+impl_extension_set!(E1);
+impl_extension_set!(E1, E2);
+impl_extension_set!(E1, E2, E3);
+impl_extension_set!(E1, E2, E3, E4);
+impl_extension_set!(E1, E2, E3, E4, E5);
+impl_extension_set!(E1, E2, E3, E4, E5, E6);
+impl_extension_set!(E1, E2, E3, E4, E5, E6, E7);
+impl_extension_set!(E1, E2, E3, E4, E5, E6, E7, E8);
+impl_extension_set!(E1, E2, E3, E4, E5, E6, E7, E8, E9);
+impl_extension_set!(E1, E2, E3, E4, E5, E6, E7, E8, E9, E10);
+
+/// Check that identifiers of `L` chain extensions are unique, which is used by [`extensions!`](crate::substrate::extensions).
+#[doc(hidden)]
+pub const fn unique_ids<C, L>() -> bool
+where
+    C: Config,
+    L: ExtensionSet<C>,
+{
+    #[allow(clippy::let_unit_value)]
+    let () = L::UNIQUE_IDS;
+
+    true
+}
+
+/// [`Extensions`] with identifiers that were checked by [`unique_ids`].
+#[doc(hidden)]
+pub trait CheckedExtensions<const UNIQUE: bool> {
+    type Extensions;
+}
+
+impl<L> CheckedExtensions<true> for L {
+    type Extensions = Extensions<L>;
+}
+
+/// Find the first identifier that is present more than once.
+const fn duplicated_id(ids: &[u16]) -> Option<u16> {
+    let mut i = 0;
+
+    while i < ids.len() {
+        let mut j = i + 1;
+
+        while j < ids.len() {
+            if ids[i] == ids[j] {
+                return Some(ids[i])
+            }
+
+            j += 1;
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// Compose chain extensions for use as `pallet_contracts::Config::ChainExtension`.
+///
+/// Expands to [`Extensions`](crate::substrate::Extensions) with a tuple of the provided chain extensions.
+/// The runtime type goes first, and the runtime fails to compile (including with `cargo check`)
+/// if any two chain extensions have the same identifier:
+///
+/// ```ignore
+/// impl pallet_contracts::Config for Runtime {
+///     type ChainExtension = obce::substrate::extensions!(Runtime; RandExtension, AssetsExtension);
+///     // ...
+/// }
+/// ```
+///
+/// You can list chain extension identifiers for debugging via [`Extensions::table`](crate::substrate::Extensions::table):
+///
+/// ```ignore
+/// type RuntimeExtensions = obce::substrate::extensions!(Runtime; RandExtension, AssetsExtension);
+///
+/// for (id, name) in RuntimeExtensions::table::<Runtime>() {
+///     println!("{id:#06x}: {name}");
+/// }
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __substrate_extensions {
+    ($runtime:ty; $($extension:ty),+ $(,)?) => {
+        <($($extension,)+) as $crate::substrate::CheckedExtensions<
+            { $crate::substrate::unique_ids::<$runtime, ($($extension,)+)>() },
+        >>::Extensions
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::duplicated_id;
+
+    #[test]
+    fn duplicated_ids() {
+        assert_eq!(duplicated_id(&[]), None);
+        assert_eq!(duplicated_id(&[1, 2, 3]), None);
+        assert_eq!(duplicated_id(&[1, 2, 3, 2]), Some(2));
+        assert_eq!(duplicated_id(&[4, 4]), Some(4));
+    }
+}
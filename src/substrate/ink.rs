@@ -0,0 +1,283 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use core::{
+    cell::{
+        RefCell,
+        RefMut,
+    },
+    marker::PhantomData,
+};
+use std::{
+    iter,
+    panic,
+    rc::Rc,
+};
+
+use frame_support::dispatch::Weight;
+use pallet_contracts::chain_extension::RetVal;
+use scale::Decode;
+
+use crate::{
+    codegen::ExtensionDescription,
+    ink_lang::{
+        env::test::ChainExtension,
+        mock::Trap,
+        replay::DIVERGED,
+    },
+    substrate::{
+        mock::MockEnvironment,
+        CallableChainExtension,
+    },
+};
+
+struct AdapterState<C, E> {
+    extension: C,
+    ext: Option<E>,
+    charged: Weight,
+}
+
+/// Adapter that registers a Substrate chain extension with the ink! off-chain engine.
+///
+/// Unlike [`#[obce::mock]`](macro@crate::mock), [`InkAdapter`] runs your
+/// [`#[obce::implementation]`](macro@crate::implementation) directly, including weight
+/// charging and error mapping, so that contract tests can't drift from the production logic.
+///
+/// Every call is executed using [`MockEnvironment`] created from the provided `ext`,
+/// and `T` is used as a runtime. Implementations with runtime bounds, such as
+/// `T: pallet_contracts::Config` or `T: frame_system::Config<Hash = H256>`, can be registered
+/// with a mock runtime created by `frame_support::construct_runtime!`, while implementations
+/// without runtime bounds can be registered with `T = ()`.
+///
+/// `pallet_contracts` doesn't allow implementing `Ext` outside of it, so your implementation
+/// should not require `E: Ext<T = T>` bounds for the adapter to be usable.
+///
+/// Calls that stop contract execution raise a [`DIVERGED`] trap, and calls that fail with
+/// a critical error raise a [`Trap`] with the error description. Both can be caught with
+/// [`catch_trap`](crate::ink_lang::mock::catch_trap).
+///
+/// ```ignore
+/// use obce::substrate::ink::InkAdapter;
+///
+/// #[ink::test]
+/// fn production_logic() {
+///     let adapter = InkAdapter::<_, _, Runtime>::register::<dyn RandExtension>(Extension::default(), ());
+///
+///     let mut contract = Contract::new();
+///     contract.fetch_random();
+///
+///     assert!(adapter.charged().ref_time() > 0);
+/// }
+/// ```
+pub struct InkAdapter<C, E, T> {
+    state: Rc<RefCell<AdapterState<C, E>>>,
+    _ghost: PhantomData<fn() -> T>,
+}
+
+impl<C, E, T> InkAdapter<C, E, T>
+where
+    C: for<'e> CallableChainExtension<E, T, &'e mut MockEnvironment<E, T>> + 'static,
+    E: 'static,
+    T: 'static,
+{
+    /// Register every method of the `D` chain extension definition, serving calls with `extension`.
+    ///
+    /// Methods inherited from supertraits are registered with their own chain extension
+    /// identifiers, and each method is also registered under its legacy identifiers.
+    pub fn register<D>(extension: C, ext: E) -> Self
+    where
+        D: ?Sized + ExtensionDescription,
+    {
+        let state = Rc::new(RefCell::new(AdapterState {
+            extension,
+            ext: Some(ext),
+            charged: Weight::zero(),
+        }));
+
+        D::for_each_method(&mut |ext_id, method| {
+            for &func_id in iter::once(&method.id).chain(method.legacy_ids) {
+                crate::ink_lang::env::test::register_chain_extension(AdapterProxy::<C, E, T> {
+                    ext_id,
+                    func_id,
+                    state: state.clone(),
+                    _ghost: PhantomData,
+                });
+            }
+        });
+
+        InkAdapter {
+            state,
+            _ghost: PhantomData,
+        }
+    }
+}
+
+impl<C, E, T> InkAdapter<C, E, T> {
+    /// Get a mutable reference to the chain extension.
+    pub fn extension(&self) -> RefMut<'_, C> {
+        RefMut::map(self.state.borrow_mut(), |state| &mut state.extension)
+    }
+
+    /// Get a mutable reference to the execution environment.
+    pub fn ext(&self) -> RefMut<'_, E> {
+        RefMut::map(self.state.borrow_mut(), |state| {
+            state.ext.as_mut().expect("execution environment is not available")
+        })
+    }
+
+    /// Total weight charged by all calls so far.
+    pub fn charged(&self) -> Weight {
+        self.state.borrow().charged
+    }
+}
+
+struct AdapterProxy<C, E, T> {
+    ext_id: u16,
+    func_id: u16,
+    state: Rc<RefCell<AdapterState<C, E>>>,
+    _ghost: PhantomData<fn() -> T>,
+}
+
+impl<C, E, T> ChainExtension for AdapterProxy<C, E, T>
+where
+    C: for<'e> CallableChainExtension<E, T, &'e mut MockEnvironment<E, T>>,
+{
+    fn func_id(&self) -> u32 {
+        (self.ext_id as u32) << 16 | (self.func_id as u32)
+    }
+
+    fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+        let input: Vec<u8> = Decode::decode(&mut &input[..]).unwrap();
+
+        let result = {
+            let mut state = self.state.borrow_mut();
+
+            let ext = state.ext.take().expect("execution environment is not available");
+            let mut env = MockEnvironment::new(ext, self.ext_id, self.func_id, input);
+
+            let result = state.extension.call(&mut env);
+
+            state.charged = state.charged.saturating_add(env.charged());
+            output.extend_from_slice(env.output());
+            state.ext = Some(env.into_ext());
+
+            result
+        };
+
+        match result {
+            Ok(RetVal::Converging(status)) => status,
+            Ok(RetVal::Diverging { .. }) => panic::panic_any(DIVERGED),
+            Err(error) => panic::panic_any(Trap(error.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use frame_support::dispatch::Weight;
+    use ink::env::chain_extension::ChainExtensionMethod;
+    use pallet_contracts::chain_extension::RetVal;
+    use scale::Encode;
+
+    use super::InkAdapter;
+    use crate::{
+        codegen::{
+            ExtensionDescription,
+            MethodInfo,
+        },
+        ink_lang::mock::{
+            catch_trap,
+            Trap,
+        },
+        substrate::{
+            CallableChainExtension,
+            ChainExtensionEnvironment,
+            CriticalError,
+        },
+    };
+
+    trait Definition {}
+
+    impl ExtensionDescription for dyn Definition {
+        const NAME: &'static str = "Definition";
+        const ID: u16 = 1;
+        const VERSION: u16 = 1;
+        const METHODS: &'static [MethodInfo] = &[MethodInfo {
+            name: "double",
+            id: 2,
+            legacy_ids: &[],
+            hash: 0,
+            args: &["value"],
+            mutable: true,
+            docs: &[],
+            signature: None,
+        }];
+    }
+
+    #[derive(Default)]
+    struct Extension {
+        calls: u32,
+    }
+
+    impl<E, Env> CallableChainExtension<E, (), Env> for Extension
+    where
+        Env: ChainExtensionEnvironment<E, ()>,
+    {
+        fn call(&mut self, mut env: Env) -> Result<RetVal, CriticalError> {
+            let len = env.in_len();
+            env.charge_weight(Weight::from_parts(10, 1))?;
+            let value: u32 = env.read_as_unbounded(len)?;
+
+            if value == 0 {
+                return Err(CriticalError::Other("ZeroValue"))
+            }
+
+            self.calls += 1;
+            env.write(&(value * 2).encode(), false, None)?;
+            Ok(RetVal::Converging(0))
+        }
+    }
+
+    fn double(value: u32) -> u32 {
+        ChainExtensionMethod::build(1 << 16 | 2)
+            .input::<u32>()
+            .output::<u32, false>()
+            .ignore_error_code()
+            .call(&value)
+    }
+
+    #[test]
+    fn calls_substrate_implementation() {
+        let adapter = InkAdapter::<_, _, ()>::register::<dyn Definition>(Extension::default(), ());
+
+        assert_eq!(double(21), 42);
+        assert_eq!(double(5), 10);
+        assert_eq!(adapter.extension().calls, 2);
+        assert_eq!(adapter.charged(), Weight::from_parts(20, 2));
+    }
+
+    #[test]
+    fn critical_errors_trap() {
+        let _adapter = InkAdapter::<_, _, ()>::register::<dyn Definition>(Extension::default(), ());
+
+        assert_eq!(catch_trap(|| double(0)), Err(Trap("ZeroValue")));
+    }
+}
@@ -0,0 +1,720 @@
+// Copyright (c) 2012-2022 Supercolony
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use core::{
+    fmt,
+    marker::PhantomData,
+};
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    env,
+    fs,
+    path::Path,
+};
+
+use frame_support::dispatch::Weight;
+use pallet_contracts::{
+    chain_extension::{
+        ChainExtension,
+        Environment,
+        Ext,
+        InitState,
+        RegisteredChainExtension,
+        Result,
+        RetVal,
+    },
+    Config,
+};
+use sp_core::{
+    Decode,
+    MaxEncodedLen,
+};
+use sp_runtime::DispatchError;
+
+use crate::{
+    recording::{
+        RecordedCall,
+        RecordedRetVal,
+    },
+    substrate::{
+        CallableChainExtension,
+        ChainExtensionEnvironment,
+        GenericChainExtension,
+    },
+};
+
+/// Environment variable that forces [`WeightSnapshot::assert_matches`] to overwrite snapshot files.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "OBCE_UPDATE_SNAPSHOTS";
+
+/// In-memory [`ChainExtensionEnvironment`] implementation.
+///
+/// [`MockEnvironment`] serves the provided input to the chain extension, stores
+/// the data that was written to the output buffer and keeps track of all charged weight.
+///
+/// Pass `&mut MockEnvironment` to your chain extension to inspect the environment
+/// after the call is complete.
+pub struct MockEnvironment<E, T> {
+    ext_id: u16,
+    func_id: u16,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    charged: Weight,
+    ext: E,
+    _ghost: PhantomData<T>,
+}
+
+impl<E, T> MockEnvironment<E, T> {
+    /// Create a new environment for a call of `func_id` method of `ext_id` chain extension.
+    ///
+    /// `input` should contain SCALE-encoded method arguments, exactly as passed by an ink! smart contract.
+    pub fn new(ext: E, ext_id: u16, func_id: u16, input: Vec<u8>) -> Self {
+        MockEnvironment {
+            ext_id,
+            func_id,
+            input,
+            output: Vec::new(),
+            charged: Weight::zero(),
+            ext,
+            _ghost: PhantomData,
+        }
+    }
+
+    /// Data written to the output buffer by the chain extension.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Total weight charged by the chain extension, including adjustments.
+    pub fn charged(&self) -> Weight {
+        self.charged
+    }
+
+    /// Consume the environment, returning the wrapped execution environment.
+    pub fn into_ext(self) -> E {
+        self.ext
+    }
+}
+
+impl<E, T> ChainExtensionEnvironment<E, T> for MockEnvironment<E, T> {
+    type ChargedAmount = Weight;
+
+    fn func_id(&self) -> u16 {
+        self.func_id
+    }
+
+    fn ext_id(&self) -> u16 {
+        self.ext_id
+    }
+
+    fn in_len(&self) -> u32 {
+        self.input.len() as u32
+    }
+
+    fn read(&self, max_len: u32) -> Result<Vec<u8>> {
+        let len = self.input.len().min(max_len as usize);
+        Ok(self.input[..len].to_vec())
+    }
+
+    fn read_into(&self, buffer: &mut &mut [u8]) -> Result<()> {
+        let len = self.input.len().min(buffer.len());
+        buffer[..len].copy_from_slice(&self.input[..len]);
+        let buffer_core = core::mem::take(buffer);
+        *buffer = &mut buffer_core[..len];
+        Ok(())
+    }
+
+    fn read_as<U: Decode + MaxEncodedLen>(&mut self) -> Result<U> {
+        let len = U::max_encoded_len().min(self.input.len());
+        U::decode(&mut &self.input[..len]).map_err(|_| DispatchError::Other("DecodingFailed"))
+    }
+
+    fn read_as_unbounded<U: Decode>(&mut self, len: u32) -> Result<U> {
+        let len = self.input.len().min(len as usize);
+        U::decode(&mut &self.input[..len]).map_err(|_| DispatchError::Other("DecodingFailed"))
+    }
+
+    fn write(&mut self, buffer: &[u8], _allow_skip: bool, weight_per_byte: Option<Weight>) -> Result<()> {
+        if let Some(per_byte) = weight_per_byte {
+            self.charge_weight(per_byte.saturating_mul(buffer.len() as u64))?;
+        }
+
+        self.output = buffer.to_vec();
+        Ok(())
+    }
+
+    fn charge_weight(&mut self, amount: Weight) -> Result<Self::ChargedAmount> {
+        self.charged = self.charged.saturating_add(amount);
+        Ok(amount)
+    }
+
+    fn adjust_weight(&mut self, charged: Self::ChargedAmount, actual_weight: Weight) {
+        self.charged = self.charged.saturating_sub(charged).saturating_add(actual_weight);
+    }
+
+    fn ext(&mut self) -> &mut E {
+        &mut self.ext
+    }
+}
+
+/// [`ChainExtensionEnvironment`] wrapper that stores the data written to the output buffer.
+///
+/// Used by [`Recorder`], all calls are delegated to the wrapped environment.
+pub struct RecordingEnvironment<Env> {
+    env: Env,
+    output: Vec<u8>,
+}
+
+impl<Env> RecordingEnvironment<Env> {
+    /// Data written to the output buffer so far.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+}
+
+impl<E, T, Env> ChainExtensionEnvironment<E, T> for RecordingEnvironment<Env>
+where
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    type ChargedAmount = Env::ChargedAmount;
+
+    fn func_id(&self) -> u16 {
+        self.env.func_id()
+    }
+
+    fn ext_id(&self) -> u16 {
+        self.env.ext_id()
+    }
+
+    fn in_len(&self) -> u32 {
+        self.env.in_len()
+    }
+
+    fn read(&self, max_len: u32) -> Result<Vec<u8>> {
+        self.env.read(max_len)
+    }
+
+    fn read_into(&self, buffer: &mut &mut [u8]) -> Result<()> {
+        self.env.read_into(buffer)
+    }
+
+    fn read_as<U: Decode + MaxEncodedLen>(&mut self) -> Result<U> {
+        self.env.read_as()
+    }
+
+    fn read_as_unbounded<U: Decode>(&mut self, len: u32) -> Result<U> {
+        self.env.read_as_unbounded(len)
+    }
+
+    fn write(&mut self, buffer: &[u8], allow_skip: bool, weight_per_byte: Option<Weight>) -> Result<()> {
+        self.env.write(buffer, allow_skip, weight_per_byte)?;
+        self.output = buffer.to_vec();
+        Ok(())
+    }
+
+    fn charge_weight(&mut self, amount: Weight) -> Result<Self::ChargedAmount> {
+        self.env.charge_weight(amount)
+    }
+
+    fn adjust_weight(&mut self, charged: Self::ChargedAmount, actual_weight: Weight) {
+        self.env.adjust_weight(charged, actual_weight)
+    }
+
+    fn ext(&mut self) -> &mut E {
+        self.env.ext()
+    }
+}
+
+/// Chain extension wrapper that records every call made to it.
+///
+/// [`Recorder`] implements [`CallableChainExtension`], as well as [`ChainExtension`] and
+/// [`RegisteredChainExtension`], by delegating calls to the wrapped chain extension,
+/// storing chain extension and method identifiers, raw input and output, as well as
+/// the returned [`RetVal`] for each successful call.
+///
+/// This allows you to register [`Recorder`] in place of your chain extension in a test runtime:
+///
+/// ```ignore
+/// impl pallet_contracts::Config for Runtime {
+///     type ChainExtension = Recorder<Extension>;
+///     // ...
+/// }
+/// ```
+///
+/// Calls that failed with a critical error are not recorded, since they revert
+/// the contract call entirely.
+///
+/// Recorded calls can be saved with [`recording::save`](crate::recording::save) and replayed in
+/// ink! off-chain tests with `obce::ink_lang::replay::Replay`:
+///
+/// ```ignore
+/// use obce::substrate::mock::Recorder;
+///
+/// let mut recorder = Recorder::new(Extension::default());
+///
+/// // Use `recorder` in place of your chain extension.
+///
+/// obce::recording::save("calls.scale", recorder.calls()).unwrap();
+/// ```
+pub struct Recorder<C> {
+    extension: C,
+    calls: Vec<RecordedCall>,
+}
+
+impl<C> Recorder<C> {
+    /// Wrap the provided chain extension.
+    pub fn new(extension: C) -> Self {
+        Recorder {
+            extension,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Calls recorded so far.
+    pub fn calls(&self) -> &[RecordedCall] {
+        &self.calls
+    }
+
+    /// Wrapped chain extension.
+    pub fn extension(&mut self) -> &mut C {
+        &mut self.extension
+    }
+
+    /// Consume the recorder, returning recorded calls.
+    pub fn into_calls(self) -> Vec<RecordedCall> {
+        self.calls
+    }
+}
+
+impl<C> Recorder<C> {
+    fn record<E, T, Env, F>(&mut self, env: Env, call: F) -> Result<RetVal>
+    where
+        Env: ChainExtensionEnvironment<E, T>,
+        F: FnOnce(&mut C, &mut RecordingEnvironment<Env>) -> Result<RetVal>,
+    {
+        let ext_id = env.ext_id();
+        let func_id = env.func_id();
+        let input = env.read(env.in_len())?;
+
+        let mut env = RecordingEnvironment {
+            env,
+            output: Vec::new(),
+        };
+
+        let ret_val = call(&mut self.extension, &mut env)?;
+
+        self.calls.push(RecordedCall {
+            ext_id,
+            func_id,
+            input,
+            output: env.output,
+            ret_val: (&ret_val).into(),
+        });
+
+        Ok(ret_val)
+    }
+}
+
+impl<C, E, T, Env> CallableChainExtension<E, T, Env> for Recorder<C>
+where
+    Env: ChainExtensionEnvironment<E, T>,
+    C: for<'r> CallableChainExtension<E, T, &'r mut RecordingEnvironment<Env>>,
+{
+    fn call(&mut self, env: Env) -> Result<RetVal> {
+        self.record(env, |extension, env| extension.call(env))
+    }
+}
+
+impl<C, T> ChainExtension<T> for Recorder<C>
+where
+    T: Config,
+    C: ChainExtension<T> + GenericChainExtension<T>,
+{
+    fn call<E: Ext<T = T>>(&mut self, env: Environment<E, InitState>) -> Result<RetVal> {
+        self.record(env.buf_in_buf_out(), |extension, env| extension.call_generic(env))
+    }
+
+    fn enabled() -> bool {
+        C::enabled()
+    }
+}
+
+impl<C, T> RegisteredChainExtension<T> for Recorder<C>
+where
+    T: Config,
+    C: RegisteredChainExtension<T> + GenericChainExtension<T>,
+{
+    const ID: u16 = C::ID;
+}
+
+impl From<&RetVal> for RecordedRetVal {
+    fn from(value: &RetVal) -> Self {
+        match value {
+            RetVal::Converging(status) => RecordedRetVal::Converging(*status),
+            RetVal::Diverging { flags, data } => {
+                RecordedRetVal::Diverging {
+                    flags: flags.bits(),
+                    data: data.clone(),
+                }
+            }
+        }
+    }
+}
+
+/// Weights charged by chain extension methods, stored by name.
+///
+/// [`WeightSnapshot`] allows you to detect unintended weight formula changes
+/// by comparing weights with a snapshot file checked into your repository:
+///
+/// ```ignore
+/// use obce::substrate::mock::{
+///     MockEnvironment,
+///     WeightSnapshot,
+/// };
+///
+/// #[test]
+/// fn weights() {
+///     let mut snapshot = WeightSnapshot::new();
+///
+///     snapshot
+///         .record(
+///             "fetch_random",
+///             &mut Extension::default(),
+///             MockEnvironment::<(), Runtime>::new(
+///                 (),
+///                 obce::id!(RandExtension),
+///                 obce::id!(RandExtension::fetch_random),
+///                 [0u8; 32].encode(),
+///             ),
+///         )
+///         .unwrap();
+///
+///     snapshot.assert_matches("weights.snap");
+/// }
+/// ```
+///
+/// To create the snapshot file or accept intended weight changes,
+/// run your tests with `OBCE_UPDATE_SNAPSHOTS=1` environment variable set.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct WeightSnapshot {
+    entries: BTreeMap<String, Weight>,
+}
+
+impl WeightSnapshot {
+    /// Create an empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call chain extension using the provided environment, and store the charged weight under `name`.
+    ///
+    /// Weight is stored even if the call failed with a critical error.
+    pub fn record<C, E, T>(&mut self, name: &str, extension: &mut C, mut env: MockEnvironment<E, T>) -> Result<RetVal>
+    where
+        C: for<'e> CallableChainExtension<E, T, &'e mut MockEnvironment<E, T>>,
+    {
+        let result = extension.call(&mut env);
+        self.insert(name, env.charged());
+        result
+    }
+
+    /// Store `weight` under `name`, replacing the previous value.
+    pub fn insert(&mut self, name: &str, weight: Weight) {
+        self.entries.insert(name.to_string(), weight);
+    }
+
+    /// Get the weight stored under `name`.
+    pub fn get(&self, name: &str) -> Option<Weight> {
+        self.entries.get(name).copied()
+    }
+
+    /// Parse a snapshot from its textual representation.
+    pub fn parse(text: &str) -> core::result::Result<Self, String> {
+        let entries = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let invalid = || format!("invalid weight snapshot line: `{line}`");
+
+                let (name, weight) = line.rsplit_once(": ").ok_or_else(invalid)?;
+                let (ref_time, proof_size) = weight.split_once(", ").ok_or_else(invalid)?;
+
+                let parse_part = |part: &str, key: &str| {
+                    part.strip_prefix(key)
+                        .and_then(|value| value.strip_prefix('='))
+                        .and_then(|value| value.parse::<u64>().ok())
+                        .ok_or_else(invalid)
+                };
+
+                let weight =
+                    Weight::from_parts(parse_part(ref_time, "ref_time")?, parse_part(proof_size, "proof_size")?);
+
+                Ok((name.to_string(), weight))
+            })
+            .collect::<core::result::Result<_, String>>()?;
+
+        Ok(Self { entries })
+    }
+
+    /// Compare the current snapshot with the `expected` one.
+    ///
+    /// Returns [`None`] if snapshots are equal, and a human-readable diff otherwise.
+    pub fn diff(&self, expected: &WeightSnapshot) -> Option<String> {
+        let mut lines = Vec::new();
+
+        let names = expected
+            .entries
+            .keys()
+            .chain(self.entries.keys())
+            .collect::<BTreeSet<_>>();
+
+        for name in names {
+            match (expected.entries.get(name), self.entries.get(name)) {
+                (Some(old), Some(new)) if old == new => {}
+                (old, new) => {
+                    if let Some(old) = old {
+                        lines.push(format!("- {}", Entry(name, old)));
+                    }
+
+                    if let Some(new) = new {
+                        lines.push(format!("+ {}", Entry(name, new)));
+                    }
+                }
+            }
+        }
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Compare the current snapshot with the one stored at `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a diff if the snapshot file contents differ from the current snapshot,
+    /// or if the file doesn't exist.
+    ///
+    /// If `OBCE_UPDATE_SNAPSHOTS` environment variable is set,
+    /// the file is overwritten with the current snapshot instead.
+    pub fn assert_matches<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+
+        if env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+            fs::write(path, self.to_string())
+                .unwrap_or_else(|error| panic!("unable to write weight snapshot {}: {error}", path.display()));
+            return
+        }
+
+        if !path.exists() {
+            panic!(
+                "weight snapshot {} does not exist, rerun with {UPDATE_SNAPSHOTS_VAR}=1 to create it",
+                path.display(),
+            );
+        }
+
+        let text = fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("unable to read weight snapshot {}: {error}", path.display()));
+
+        let expected = Self::parse(&text).unwrap_or_else(|error| panic!("{error}"));
+
+        if let Some(diff) = self.diff(&expected) {
+            panic!(
+                "weight snapshot {} does not match charged weights:\n{diff}\n\n\
+                 rerun with {UPDATE_SNAPSHOTS_VAR}=1 to accept the changes",
+                path.display(),
+            );
+        }
+    }
+}
+
+impl fmt::Display for WeightSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, weight) in &self.entries {
+            writeln!(f, "{}", Entry(name, weight))?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Entry<'a>(&'a str, &'a Weight);
+
+impl<'a> fmt::Display for Entry<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: ref_time={}, proof_size={}",
+            self.0,
+            self.1.ref_time(),
+            self.1.proof_size()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use frame_support::dispatch::Weight;
+    use pallet_contracts::chain_extension::RetVal;
+    use sp_core::Encode;
+
+    use super::{
+        MockEnvironment,
+        Recorder,
+        WeightSnapshot,
+    };
+    use crate::{
+        recording::{
+            RecordedCall,
+            RecordedRetVal,
+        },
+        substrate::{
+            CallableChainExtension,
+            ChainExtensionEnvironment,
+            CriticalError,
+        },
+    };
+
+    struct Extension;
+
+    impl<E, Env> CallableChainExtension<E, (), Env> for Extension
+    where
+        Env: ChainExtensionEnvironment<E, ()>,
+    {
+        fn call(&mut self, mut env: Env) -> Result<RetVal, CriticalError> {
+            let len = env.in_len();
+            let charged = env.charge_weight(Weight::from_parts(1_000, 10))?;
+            let value: u32 = env.read_as_unbounded(len)?;
+            env.adjust_weight(charged, Weight::from_parts(value as u64, 10));
+            env.write(&(value * 2).encode(), false, None)?;
+            Ok(RetVal::Converging(0))
+        }
+    }
+
+    #[test]
+    fn mock_environment_records_output_and_weight() {
+        let mut env = MockEnvironment::<(), ()>::new((), 1, 2, 21u32.encode());
+
+        assert!(matches!(Extension.call(&mut env), Ok(RetVal::Converging(0))));
+        assert_eq!(env.output(), &42u32.encode()[..]);
+        assert_eq!(env.charged(), Weight::from_parts(21, 10));
+    }
+
+    #[test]
+    fn recorder_records_calls() {
+        let mut recorder = Recorder::new(Extension);
+
+        assert!(matches!(
+            recorder.call(MockEnvironment::<(), ()>::new((), 1, 2, 21u32.encode())),
+            Ok(RetVal::Converging(0))
+        ));
+        assert!(matches!(
+            recorder.call(MockEnvironment::<(), ()>::new((), 1, 3, 5u32.encode())),
+            Ok(RetVal::Converging(0))
+        ));
+
+        assert_eq!(
+            recorder.into_calls(),
+            vec![
+                RecordedCall {
+                    ext_id: 1,
+                    func_id: 2,
+                    input: 21u32.encode(),
+                    output: 42u32.encode(),
+                    ret_val: RecordedRetVal::Converging(0),
+                },
+                RecordedCall {
+                    ext_id: 1,
+                    func_id: 3,
+                    input: 5u32.encode(),
+                    output: 10u32.encode(),
+                    ret_val: RecordedRetVal::Converging(0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_records_weight() {
+        let mut snapshot = WeightSnapshot::new();
+
+        snapshot
+            .record(
+                "method",
+                &mut Extension,
+                MockEnvironment::<(), ()>::new((), 1, 2, 5u32.encode()),
+            )
+            .unwrap();
+
+        assert_eq!(snapshot.get("method"), Some(Weight::from_parts(5, 10)));
+    }
+
+    #[test]
+    fn snapshot_roundtrip() {
+        let mut snapshot = WeightSnapshot::new();
+        snapshot.insert("first", Weight::from_parts(1, 2));
+        snapshot.insert("second", Weight::from_parts(3, 4));
+
+        let text = snapshot.to_string();
+        assert_eq!(
+            text,
+            "first: ref_time=1, proof_size=2\nsecond: ref_time=3, proof_size=4\n"
+        );
+        assert_eq!(WeightSnapshot::parse(&text), Ok(snapshot));
+    }
+
+    #[test]
+    fn snapshot_diff() {
+        let mut expected = WeightSnapshot::new();
+        expected.insert("changed", Weight::from_parts(1, 0));
+        expected.insert("removed", Weight::from_parts(2, 0));
+        expected.insert("same", Weight::from_parts(3, 0));
+
+        let mut current = WeightSnapshot::new();
+        current.insert("added", Weight::from_parts(4, 0));
+        current.insert("changed", Weight::from_parts(5, 0));
+        current.insert("same", Weight::from_parts(3, 0));
+
+        assert_eq!(expected.diff(&expected), None);
+        assert_eq!(
+            current.diff(&expected).unwrap(),
+            "+ added: ref_time=4, proof_size=0\n\
+             - changed: ref_time=1, proof_size=0\n\
+             + changed: ref_time=5, proof_size=0\n\
+             - removed: ref_time=2, proof_size=0"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "does not exist, rerun with OBCE_UPDATE_SNAPSHOTS=1 to create it")]
+    fn snapshot_is_not_created_implicitly() {
+        let path = std::env::temp_dir().join("obce-missing-weights.snap");
+
+        WeightSnapshot::new().assert_matches(path);
+    }
+
+    #[test]
+    fn snapshot_parse_rejects_invalid_lines() {
+        assert!(WeightSnapshot::parse("method: ref_time=1").is_err());
+        assert!(WeightSnapshot::parse("method: ref_time=a, proof_size=1").is_err());
+    }
+}
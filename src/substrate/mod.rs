@@ -20,9 +20,26 @@
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 mod environment;
+mod extensions;
 mod is_critical_error;
 
+/// Utilities for testing chain extensions on the Substrate side.
+#[cfg(feature = "std")]
+pub mod mock;
+
+/// Running Substrate chain extension implementations in ink! off-chain tests.
+#[cfg(all(feature = "std", feature = "ink"))]
+pub mod ink;
+
+#[doc(inline)]
+pub use crate::__substrate_extensions as extensions;
 pub use environment::ChainExtensionEnvironment;
+pub use extensions::{
+    unique_ids,
+    CheckedExtensions,
+    ExtensionSet,
+    Extensions,
+};
 pub use frame_support;
 pub use frame_system;
 pub use is_critical_error::{
@@ -36,9 +53,20 @@ pub use sp_std;
 
 use core::marker::PhantomData;
 
-use pallet_contracts::chain_extension::RetVal;
+use frame_support::traits::Currency;
+use pallet_contracts::chain_extension::{
+    Ext,
+    RetVal,
+};
 use sp_runtime::DispatchError;
 
+/// Balance type of the runtime, as used by `pallet_contracts`.
+///
+/// This is the default runtime type of `Balance` associated type in
+/// [`#[obce::definition]`](macro@crate::definition) traits.
+pub type BalanceOf<T> =
+    <<T as pallet_contracts::Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
 /// Callable chain extension with generalized environment information.
 ///
 /// Unlike [`ChainExtension`](pallet_contracts::chain_extension::ChainExtension), [`CallableChainExtension`]
@@ -52,6 +80,55 @@ pub trait CallableChainExtension<E, T, Env> {
     fn call(&mut self, env: Env) -> Result<RetVal, CriticalError>;
 }
 
+/// Chain extension that can be called with any [`ChainExtensionEnvironment`] implementation.
+///
+/// Unlike [`CallableChainExtension`], the environment type is chosen by the caller,
+/// which allows wrappers like [`Recorder`](mock::Recorder) to implement
+/// [`ChainExtension`](pallet_contracts::chain_extension::ChainExtension) by passing
+/// their own environment to the wrapped chain extension.
+///
+/// This trait is automatically implemented on your Substrate chain extension struct
+/// with [`#[obce::implementation]`](macro@crate::implementation) expansion.
+pub trait GenericChainExtension<T> {
+    /// Call chain extension with the provided [`ChainExtensionEnvironment`] implementation.
+    fn call_generic<E, Env>(&mut self, env: Env) -> Result<RetVal, CriticalError>
+    where
+        E: Ext<T = T>,
+        Env: ChainExtensionEnvironment<E, T>;
+}
+
+/// Dispatch of chain extension methods of the `D` chain extension definition,
+/// including methods inherited from its supertraits.
+///
+/// This trait is automatically implemented on your Substrate chain extension struct
+/// with [`#[obce::implementation]`](macro@crate::implementation) expansion.
+pub trait Dispatch<D: ?Sized, E, T, Env> {
+    /// Whether a method with the provided chain extension and method identifiers can be dispatched.
+    fn handles(ext_id: u16, func_id: u16) -> bool;
+
+    /// Dispatch the call to the method with the identifier provided by [`ChainExtensionEnvironment`].
+    ///
+    /// Calls are assumed to be addressed to `D`, unless one of its supertraits
+    /// with a different chain extension identifier handles the call.
+    fn dispatch(&mut self, env: Env) -> Result<RetVal, CriticalError>;
+}
+
+/// Dispatch of chain extension methods inherited from supertraits of a chain extension definition.
+///
+/// This trait is automatically implemented with [`#[obce::definition]`](macro@crate::definition) expansion,
+/// and is used by [`Dispatch`] implementations for identifiers of methods not defined
+/// by the chain extension itself.
+pub trait DispatchSupertraits<Extension, E, T, Env> {
+    /// Whether a method with the provided chain extension and method identifiers
+    /// is provided by any of the supertraits.
+    fn handles(ext_id: u16, func_id: u16) -> bool;
+
+    /// Dispatch the call to the supertrait that provides the method.
+    ///
+    /// Returns an error if none of the supertraits provide the method.
+    fn dispatch(extension: &mut Extension, env: Env) -> Result<RetVal, CriticalError>;
+}
+
 /// Chain extension context that you can use with your implementations.
 pub struct ExtensionContext<'a, E, T, Env, Extension>
 where
@@ -0,0 +1,121 @@
+use obce::ink_lang::env::DefaultEnvironment;
+
+#[obce::definition(id = 123)]
+pub trait Trait {
+    fn method(&self) -> u32;
+}
+
+#[obce::ink_lang::extension]
+#[derive(Debug, PartialEq, Eq)]
+pub struct TestExtension;
+
+impl Trait for TestExtension {}
+
+#[obce::definition(id = 124, ink_struct = "CounterExtension")]
+pub trait Counter {
+    fn increment(&mut self, val: u32) -> u32;
+}
+
+obce::ink_lang::environment!(
+    pub ComposedEnvironment: DefaultEnvironment,
+    extensions = [CounterExtension, custom: TestExtension],
+);
+
+#[ink::contract(env = crate::ComposedEnvironment)]
+mod composed_contract {
+    use crate::{
+        Counter,
+        Trait,
+    };
+
+    #[ink(storage)]
+    pub struct ComposedContract {}
+
+    impl ComposedContract {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            ComposedContract {}
+        }
+
+        #[ink(message)]
+        pub fn call_method(&self) -> u32 {
+            self.env().extension().custom().method()
+        }
+
+        #[ink(message)]
+        pub fn increment(&mut self, val: u32) -> u32 {
+            self.env().extension().counter().increment(val)
+        }
+    }
+}
+
+mod composed_environment {
+    use obce::ink_lang::{
+        env::{
+            DefaultEnvironment,
+            Environment,
+        },
+        ChainExtensionInstance,
+    };
+
+    use crate::{
+        ComposedEnvironment,
+        ComposedEnvironmentExtensions,
+        CounterExtension,
+        TestExtension,
+    };
+
+    #[derive(Default)]
+    pub struct State {
+        value: u32,
+    }
+
+    #[obce::mock(register_fn = false)]
+    impl crate::Trait for State {
+        fn method(&self) -> u32 {
+            self.value
+        }
+    }
+
+    #[obce::mock(register_fn = false)]
+    impl crate::Counter for State {
+        fn increment(&mut self, val: u32) -> u32 {
+            self.value += val;
+            self.value
+        }
+    }
+
+    #[test]
+    fn environment_types() {
+        fn assert_same<A: 'static, B: 'static>() {
+            assert_eq!(std::any::TypeId::of::<A>(), std::any::TypeId::of::<B>());
+        }
+
+        assert_same::<<ComposedEnvironment as Environment>::AccountId, <DefaultEnvironment as Environment>::AccountId>(
+        );
+        assert_same::<<ComposedEnvironment as Environment>::Balance, <DefaultEnvironment as Environment>::Balance>();
+        assert_same::<<ComposedEnvironment as Environment>::ChainExtension, ComposedEnvironmentExtensions>();
+        assert_eq!(
+            ComposedEnvironment::MAX_EVENT_TOPICS,
+            DefaultEnvironment::MAX_EVENT_TOPICS
+        );
+
+        let extensions = ComposedEnvironmentExtensions::instantiate();
+        assert_eq!(extensions.counter(), CounterExtension);
+        assert_eq!(extensions.custom(), TestExtension);
+    }
+
+    #[test]
+    fn call_contract() {
+        let (_, counter) = obce::register_mocks!(
+            State { value: 7 } => dyn crate::Trait,
+            State::default() => dyn crate::Counter,
+        );
+
+        let mut contract = crate::composed_contract::ComposedContract::new();
+        assert_eq!(contract.call_method(), 7);
+        assert_eq!(contract.increment(2), 2);
+        assert_eq!(contract.increment(3), 5);
+        assert_eq!(counter.borrow().value, 5);
+    }
+}
@@ -0,0 +1,175 @@
+#[path = "ui/substrate/extensions/runtime.rs"]
+mod runtime;
+
+use obce::substrate::{
+    frame_support::traits::Randomness,
+    frame_system::Config as SysConfig,
+    ink::InkAdapter,
+    pallet_contracts::Config as ContractConfig,
+    sp_core::H256,
+    sp_runtime::traits::{
+        BlakeTwo256,
+        Hash,
+        StaticLookup,
+    },
+    ChainExtensionEnvironment,
+    ExtensionContext,
+};
+use runtime::Runtime;
+
+#[obce::definition(id = 1)]
+pub trait BaseDefinition {
+    #[obce(id = 1)]
+    fn calls(&self) -> u32;
+}
+
+#[obce::definition(id = 2)]
+pub trait Definition: BaseDefinition {
+    #[obce(id = 1, legacy_ids(10))]
+    fn double(&mut self, val: u32) -> u32;
+}
+
+#[derive(Default)]
+pub struct Extension {
+    calls: u32,
+}
+
+#[obce::implementation(supertrait)]
+impl<'a, E, T, Env> BaseDefinition for ExtensionContext<'a, E, T, Env, Extension>
+where
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn calls(&self) -> u32 {
+        self.storage.calls
+    }
+}
+
+#[obce::implementation]
+impl<'a, E, T, Env> Definition for ExtensionContext<'a, E, T, Env, Extension>
+where
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn double(&mut self, val: u32) -> u32 {
+        self.storage.calls += 1;
+        val * 2
+    }
+}
+
+#[obce::definition(id = 3)]
+pub trait RandExtension {
+    #[obce(id = 1)]
+    fn fetch_random(&self, subject: [u8; 32]) -> [u8; 32];
+}
+
+#[derive(Default)]
+pub struct Rand;
+
+#[obce::implementation]
+impl<'a, E, T, Env> RandExtension for ExtensionContext<'a, E, T, Env, Rand>
+where
+    T: SysConfig<Hash = H256> + ContractConfig,
+    <<T as SysConfig>::Lookup as StaticLookup>::Source: From<<T as SysConfig>::AccountId>,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn fetch_random(&self, subject: [u8; 32]) -> [u8; 32] {
+        T::Randomness::random(&subject).0.to_fixed_bytes()
+    }
+}
+
+#[ink::contract]
+mod adapter_contract {
+    use ink::env::chain_extension::ChainExtensionMethod;
+
+    use crate::{
+        BaseDefinition,
+        Definition,
+        RandExtension,
+    };
+
+    #[ink(storage)]
+    pub struct AdapterContract {}
+
+    impl AdapterContract {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            AdapterContract {}
+        }
+
+        /// Call `double` using the provided method identifier.
+        #[ink(message)]
+        pub fn double(&mut self, func_id: u16, val: u32) -> u32 {
+            ChainExtensionMethod::build((obce::id!(Definition) as u32) << 16 | func_id as u32)
+                .input::<u32>()
+                .output::<u32, false>()
+                .ignore_error_code()
+                .call(&val)
+        }
+
+        #[ink(message)]
+        pub fn calls(&self) -> u32 {
+            ChainExtensionMethod::build(
+                (obce::id!(BaseDefinition) as u32) << 16 | obce::id!(BaseDefinition::calls) as u32,
+            )
+            .input::<()>()
+            .output::<u32, false>()
+            .ignore_error_code()
+            .call(&())
+        }
+
+        #[ink(message)]
+        pub fn fetch_random(&self, subject: [u8; 32]) -> [u8; 32] {
+            ChainExtensionMethod::build(
+                (obce::id!(RandExtension) as u32) << 16 | obce::id!(RandExtension::fetch_random) as u32,
+            )
+            .input::<[u8; 32]>()
+            .output::<[u8; 32], false>()
+            .ignore_error_code()
+            .call(&subject)
+        }
+    }
+}
+
+use adapter_contract::AdapterContract;
+
+fn register() -> InkAdapter<Extension, (), ()> {
+    InkAdapter::register::<dyn Definition>(Extension::default(), ())
+}
+
+#[test]
+fn calls_implementation() {
+    let adapter = register();
+
+    let mut contract = AdapterContract::new();
+    assert_eq!(contract.double(obce::id!(Definition::double), 21), 42);
+    assert_eq!(adapter.extension().calls, 1);
+}
+
+#[test]
+fn calls_legacy_ids() {
+    let adapter = register();
+
+    let mut contract = AdapterContract::new();
+    assert_eq!(contract.double(10, 21), 42);
+    assert_eq!(adapter.extension().calls, 1);
+}
+
+#[test]
+fn calls_supertrait_methods() {
+    let _adapter = register();
+
+    let mut contract = AdapterContract::new();
+    contract.double(obce::id!(Definition::double), 1);
+    contract.double(obce::id!(Definition::double), 2);
+    assert_eq!(contract.calls(), 2);
+}
+
+#[test]
+fn calls_implementation_with_runtime_bounds() {
+    let _adapter = InkAdapter::<_, _, Runtime>::register::<dyn RandExtension>(Rand, ());
+
+    let contract = AdapterContract::new();
+    assert_eq!(
+        contract.fetch_random([1; 32]),
+        BlakeTwo256::hash(&[1; 32]).to_fixed_bytes()
+    );
+}
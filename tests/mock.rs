@@ -1,9 +1,61 @@
-#[obce::definition(id = 123)]
+#[obce::error(require_ret_val = false)]
+pub enum Error {
+    #[obce(ret_val = "100")]
+    WithRetVal,
+
+    WithoutRetVal,
+}
+
+#[obce::definition(id = 123, call_derive(Debug, Clone, PartialEq, Eq))]
 pub trait Trait {
+    /// Sums two values.
+    ///
+    /// Used by most of the tests.
     fn method(&mut self, val: u32, another_val: u32) -> u32;
 
     #[obce(id = 456)]
     fn another_method(&mut self, val: u32) -> u32;
+
+    fn fallible_method(&mut self, val: u32) -> Result<u32, Error>;
+}
+
+#[obce::definition(id = 124)]
+pub trait AnotherTrait {
+    fn other_method(&mut self, val: u32) -> u32;
+
+    fn read_only(&self) -> u32;
+}
+
+#[obce::definition(id = 123)]
+pub trait ChildTrait: Trait {
+    fn child_method(&mut self, val: u32) -> u32;
+}
+
+#[obce::definition(id = 125)]
+pub trait SeparateChildTrait: AnotherTrait {
+    fn separate_child_method(&self) -> u32;
+}
+
+#[obce::definition(id = 126)]
+pub trait AssetsTrait {
+    type AccountId;
+
+    type Balance;
+
+    #[obce(ink = "u32", substrate = "u32")]
+    type AssetId;
+
+    fn balance_of(&self, asset: Self::AssetId, owner: Self::AccountId) -> Self::Balance;
+
+    #[obce(ink_only)]
+    fn has_balance(&self, asset: Self::AssetId, owner: Self::AccountId) -> bool {
+        self.balance_of(asset, owner) > 0
+    }
+}
+
+#[obce::definition(id = 127, ink_struct = "StorageExtension")]
+pub trait StorageTrait {
+    fn store(&mut self, key: &str, value: &[u8]) -> u32;
 }
 
 #[obce::ink_lang::extension]
@@ -11,9 +63,33 @@ struct TestExtension;
 
 impl Trait for TestExtension {}
 
+impl AnotherTrait for TestExtension {}
+
+impl ChildTrait for TestExtension {}
+
+impl SeparateChildTrait for TestExtension {}
+
+impl AssetsTrait for TestExtension {}
+
+pub struct StatusCode(u32);
+
+impl ink::env::chain_extension::FromStatusCode for StatusCode {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            code => Err(Self(code)),
+        }
+    }
+}
+
 #[ink::contract]
 mod simple_contract {
+    use ink::env::chain_extension::ChainExtensionMethod;
+
     use crate::{
+        AnotherTrait,
+        Error,
+        StatusCode,
         TestExtension,
         Trait,
     };
@@ -36,6 +112,24 @@ mod simple_contract {
         pub fn call_another_method(&mut self, val: u32) -> u32 {
             TestExtension.another_method(val)
         }
+
+        #[ink(message)]
+        pub fn call_another_trait_method(&mut self, val: u32) -> u32 {
+            TestExtension.other_method(val)
+        }
+
+        /// Call `fallible_method`, returning the status code as an error.
+        #[ink(message)]
+        pub fn call_fallible_method(&mut self, val: u32) -> Result<Result<u32, Error>, u32> {
+            let id = (obce::id!(Trait) as u32) << 16 | (obce::id!(Trait::fallible_method) as u32);
+
+            ChainExtensionMethod::build(id)
+                .input::<u32>()
+                .output::<Result<u32, Error>, false>()
+                .handle_error_code::<StatusCode>()
+                .call(&val)
+                .map_err(|StatusCode(code)| code)
+        }
     }
 }
 
@@ -76,6 +170,33 @@ mod state_access {
         assert_eq!(contract.call_method(100, 200), 1);
         assert_eq!(contract.call_method(100, 200), 2);
     }
+
+    #[test]
+    fn inspect_state() {
+        let mock = register_chain_extensions(State::default());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_method(100, 200);
+        contract.call_method(100, 200);
+        assert_eq!(mock.borrow().call_count, 2);
+
+        mock.borrow_mut().call_count = 10;
+        assert_eq!(contract.call_method(100, 200), 11);
+    }
+
+    #[test]
+    fn replace_state() {
+        let mock = register_chain_extensions(State::default());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_method(100, 200);
+
+        let previous = mock.replace(State { call_count: 5 });
+        assert_eq!(previous.call_count, 1);
+        assert_eq!(contract.call_method(100, 200), 6);
+
+        let previous = mock.reset();
+        assert_eq!(previous.call_count, 6);
+        assert_eq!(contract.call_method(100, 200), 1);
+    }
 }
 
 mod cross_method_state {
@@ -132,10 +253,1045 @@ mod unregistered_call {
     impl crate::Trait for () {}
 
     #[test]
-    #[should_panic(expected = "UnregisteredChainExtension")]
+    #[should_panic(expected = "`another_method` is not implemented by the mock")]
     fn call_contract() {
         register_chain_extensions(());
         let mut contract = crate::simple_contract::SimpleContract::new();
         contract.call_another_method(200);
     }
 }
+
+mod fallback {
+    use obce::ink_lang::mock::{
+        catch_trap,
+        Fallback,
+        Weight,
+        OUT_OF_GAS,
+    };
+
+    use crate::{
+        Error,
+        Trait,
+    };
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        fn method(&mut self, val: u32, another_val: u32) -> u32 {
+            val + another_val
+        }
+    }
+
+    #[test]
+    fn default() {
+        let mock = register_chain_extensions(());
+        mock.set_fallback(Fallback::Default);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_method(1, 2), 3);
+        assert_eq!(contract.call_another_method(200), 0);
+        assert_eq!(mock.calls().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "output of `fallible_method` does not implement `Default`")]
+    fn default_not_implemented() {
+        let mock = register_chain_extensions(());
+        mock.set_fallback(Fallback::Default);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_fallible_method(1).ok();
+    }
+
+    #[test]
+    fn error() {
+        let mock = register_chain_extensions(());
+        mock.set_fallback(Fallback::error(Error::WithoutRetVal));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_fallible_method(1), Ok(Err(Error::WithoutRetVal)));
+    }
+
+    #[test]
+    fn error_without_ret_val_opt_in() {
+        let mock = register_chain_extensions(());
+        mock.set_fallback(Fallback::error(Error::WithRetVal));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_fallible_method(1), Ok(Err(Error::WithRetVal)));
+    }
+
+    #[test]
+    fn error_with_ret_val() {
+        let mock = register_chain_extensions(());
+        mock.set_fallback(Fallback::error_ret_val(Error::WithRetVal));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_fallible_method(1), Err(100));
+    }
+
+    #[test]
+    fn out_of_gas() {
+        let mock = register_chain_extensions(());
+        mock.set_fallback(Fallback::Default);
+        mock.set_fallback_weight(Weight::from_parts(10, 0));
+        mock.set_gas_limit(Weight::from_parts(15, 0));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_another_method(200), 0);
+        assert_eq!(mock.consumed_weight(), Weight::from_parts(10, 0));
+        assert_eq!(catch_trap(|| contract.call_another_method(200)), Err(OUT_OF_GAS));
+        assert_eq!(mock.consumed_weight(), Weight::from_parts(10, 0));
+    }
+
+    #[test]
+    fn handler() {
+        let mock = register_chain_extensions(());
+        mock.set_fallback(Fallback::handler(|func_id, input| {
+            assert_eq!(func_id & 0xFFFF, obce::id!(Trait::another_method) as u32);
+            // Echo the input back.
+            input.to_vec()
+        }));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_another_method(200), 200);
+    }
+}
+
+mod ret_val {
+    use crate::Error;
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        #[obce(ret_val)]
+        fn fallible_method(&mut self, val: u32) -> Result<u32, Error> {
+            match val {
+                0 => Err(Error::WithRetVal),
+                1 => Err(Error::WithoutRetVal),
+                val => Ok(val),
+            }
+        }
+    }
+
+    #[test]
+    fn call_contract() {
+        register_chain_extensions(());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_fallible_method(0), Err(100));
+        assert_eq!(contract.call_fallible_method(1), Ok(Err(Error::WithoutRetVal)));
+        assert_eq!(contract.call_fallible_method(2), Ok(Ok(2)));
+    }
+}
+
+mod without_ret_val {
+    use crate::Error;
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        fn fallible_method(&mut self, _: u32) -> Result<u32, Error> {
+            Err(Error::WithRetVal)
+        }
+    }
+
+    #[test]
+    fn call_contract() {
+        register_chain_extensions(());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_fallible_method(0), Ok(Err(Error::WithRetVal)));
+    }
+}
+
+mod recorded_calls {
+    use crate::Trait;
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        fn method(&mut self, val: u32, another_val: u32) -> u32 {
+            val + another_val
+        }
+
+        fn another_method(&mut self, val: u32) -> u32 {
+            val
+        }
+    }
+
+    #[test]
+    fn call_contract() {
+        let mock = register_chain_extensions(());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_method(1, 2);
+        contract.call_another_method(3);
+        contract.call_method(4, 5);
+
+        assert_eq!(mock.method_calls(), vec![(1, 2), (4, 5)]);
+        assert_eq!(mock.another_method_calls(), vec![3]);
+
+        let calls = mock.calls();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[1].ext_id(), obce::id!(Trait));
+        assert_eq!(calls[1].method_id(), obce::id!(Trait::another_method));
+    }
+}
+
+mod expectations {
+    #[obce::mock]
+    impl crate::Trait for () {
+        fn method(&mut self, val: u32, another_val: u32) -> u32 {
+            val + another_val
+        }
+
+        fn another_method(&mut self, val: u32) -> u32;
+    }
+
+    #[test]
+    fn returning() {
+        let mock = register_chain_extensions(());
+        mock.expect_another_method().with(1).times(2).returning(|val| val * 10);
+        mock.expect_another_method().return_const(0);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_another_method(1), 10);
+        assert_eq!(contract.call_another_method(2), 0);
+        assert_eq!(contract.call_another_method(1), 10);
+    }
+
+    #[test]
+    fn fallback_to_body() {
+        let mock = register_chain_extensions(());
+        let expectation = mock.expect_method().withf(|(val, _)| *val > 10).times(1);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_method(100, 200), 300);
+        assert_eq!(contract.call_method(1, 2), 3);
+        assert_eq!(expectation.call_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "`another_method` was expected to be called 2 times, but was called 1 times")]
+    fn unsatisfied() {
+        let mock = register_chain_extensions(());
+        mock.expect_another_method().times(2).return_const(0);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_another_method(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "`another_method` was called more times than expected (2 times)")]
+    fn called_too_many_times() {
+        let mock = register_chain_extensions(());
+        mock.expect_another_method().times(1).return_const(0);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_another_method(1);
+        contract.call_another_method(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected call to `another_method`")]
+    fn without_expectation() {
+        let _mock = register_chain_extensions(());
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_another_method(1);
+    }
+
+    #[test]
+    fn checkpoint() {
+        let mock = register_chain_extensions(());
+        mock.expect_method().times(1);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_method(1, 2);
+        mock.checkpoint();
+
+        contract.call_method(1, 2);
+        assert_eq!(mock.method_calls().len(), 2);
+    }
+}
+
+mod multiple_extensions {
+    use obce::ink_lang::Mock;
+
+    use crate::{
+        AnotherTrait,
+        Trait,
+    };
+
+    #[derive(Default)]
+    pub struct State {
+        call_count: u32,
+    }
+
+    #[obce::mock(register_fn = false)]
+    impl crate::Trait for State {
+        fn method(&mut self, val: u32, another_val: u32) -> u32 {
+            self.call_count += 1;
+            val + another_val
+        }
+    }
+
+    #[obce::mock(register_fn = false)]
+    impl crate::AnotherTrait for State {
+        fn other_method(&mut self, val: u32) -> u32;
+    }
+
+    #[test]
+    fn register() {
+        let first = Mock::<dyn Trait>::register(State::default());
+        let second = Mock::<dyn AnotherTrait>::register(State::default());
+        second.expect_other_method().return_const(42);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_method(1, 2), 3);
+        assert_eq!(contract.call_another_trait_method(1), 42);
+        assert_eq!(first.borrow().call_count, 1);
+    }
+
+    #[test]
+    fn register_mocks() {
+        let (first, second) = obce::register_mocks!(
+            State::default() => dyn Trait,
+            State::default() => dyn AnotherTrait,
+        );
+        second.expect_other_method().with(5).times(1).returning(|val| val * 2);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_another_trait_method(5), 10);
+        assert_eq!(contract.call_method(1, 2), 3);
+        assert_eq!(first.borrow().call_count, 1);
+    }
+}
+
+mod default_registration {
+    #[derive(Default)]
+    pub struct First {
+        call_count: u32,
+    }
+
+    #[derive(Default)]
+    pub struct Second;
+
+    #[obce::mock]
+    impl crate::Trait for First {
+        fn method(&mut self, val: u32, another_val: u32) -> u32 {
+            self.call_count += 1;
+            val + another_val
+        }
+    }
+
+    #[obce::mock]
+    impl crate::Trait for Second {
+        fn method(&mut self, val: u32, another_val: u32) -> u32;
+    }
+
+    #[obce::mock]
+    impl crate::AnotherTrait for () {
+        fn other_method(&mut self, val: u32) -> u32 {
+            val * 3
+        }
+    }
+
+    #[test]
+    fn register() {
+        let first = register_first(First::default());
+        let _another = register_another_trait(());
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_method(1, 2), 3);
+        assert_eq!(contract.call_another_trait_method(2), 6);
+        assert_eq!(first.borrow().call_count, 1);
+    }
+
+    #[test]
+    fn same_trait() {
+        let second = register_second_trait(Second);
+        second.expect_method().with((1, 2)).return_const(10);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_method(1, 2), 10);
+        assert_eq!(second.method_calls(), vec![(1, 2)]);
+    }
+}
+
+mod supertraits {
+    use obce::codegen::ExtensionDescription;
+
+    use self::same_id::ChildTraitExpectations;
+    use crate::{
+        AnotherTrait,
+        ChildTrait,
+        SeparateChildTrait,
+        TestExtension,
+        Trait,
+    };
+
+    mod same_id {
+        #[obce::mock]
+        impl crate::ChildTrait for () {
+            fn method(&mut self, val: u32, another_val: u32) -> u32 {
+                val + another_val
+            }
+
+            fn another_method(&mut self, val: u32) -> u32;
+
+            fn child_method(&mut self, val: u32) -> u32 {
+                val * 2
+            }
+        }
+
+        pub use register_chain_extensions as register;
+    }
+
+    mod separate_id {
+        #[obce::mock]
+        impl crate::SeparateChildTrait for () {
+            fn read_only(&self) -> u32 {
+                1
+            }
+
+            fn separate_child_method(&self) -> u32 {
+                2
+            }
+        }
+
+        pub use register_chain_extensions as register;
+    }
+
+    #[test]
+    fn inherited_methods() {
+        let mock = same_id::register(());
+        mock.expect_another_method().with(1).return_const(7);
+
+        let mut extension = TestExtension;
+        assert_eq!(Trait::method(&mut extension, 1, 2), 3);
+        assert_eq!(Trait::another_method(&mut extension, 1), 7);
+        assert_eq!(ChildTrait::child_method(&mut extension, 2), 4);
+        assert_eq!(mock.method_calls(), vec![(1, 2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`fallible_method` is not implemented by the mock")]
+    fn inherited_fallback() {
+        let _mock = same_id::register(());
+
+        Trait::fallible_method(&mut TestExtension, 1).unwrap();
+    }
+
+    #[test]
+    fn inherited_methods_under_separate_id() {
+        let _mock = separate_id::register(());
+
+        assert_eq!(AnotherTrait::read_only(&TestExtension), 1);
+        assert_eq!(SeparateChildTrait::separate_child_method(&TestExtension), 2);
+    }
+
+    #[test]
+    fn for_each_method() {
+        let mut methods = vec![];
+
+        <dyn SeparateChildTrait as ExtensionDescription>::for_each_method(&mut |ext_id, method| {
+            methods.push((ext_id, method.name));
+        });
+
+        assert_eq!(
+            methods,
+            vec![
+                (125, "separate_child_method"),
+                (124, "other_method"),
+                (124, "read_only")
+            ]
+        );
+    }
+}
+
+mod mock_env {
+    use ink::{
+        env::{
+            test,
+            DefaultEnvironment,
+        },
+        primitives::AccountId,
+    };
+    use obce::ink_lang::mock::MockEnv;
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        fn method(&mut self, _: u32, _: u32) -> u32 {
+            assert_eq!(MockEnv::caller_of::<DefaultEnvironment>(), AccountId::from([5; 32]));
+            assert_eq!(MockEnv::callee(), AccountId::from([6; 32]));
+            MockEnv::block_number()
+        }
+    }
+
+    #[test]
+    fn captured_state() {
+        register_chain_extensions(());
+        MockEnv::capture_accounts();
+        test::set_caller::<DefaultEnvironment>(AccountId::from([5; 32]));
+        test::set_callee::<DefaultEnvironment>(AccountId::from([6; 32]));
+        test::advance_block::<DefaultEnvironment>();
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_method(0, 0), 1);
+    }
+}
+
+mod traps {
+    use obce::ink_lang::{
+        mock::catch_trap,
+        Trap,
+    };
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        fn method(&mut self, val: u32, _: u32) -> Result<u32, Trap> {
+            match val {
+                0 => Err(Trap("zero is not allowed")),
+                val => Ok(val),
+            }
+        }
+
+        fn another_method(&mut self, val: u32) -> u32;
+    }
+
+    #[test]
+    fn trap_from_method() {
+        register_chain_extensions(());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(catch_trap(|| contract.call_method(1, 0)), Ok(1));
+        assert_eq!(
+            catch_trap(|| contract.call_method(0, 0)),
+            Err(Trap("zero is not allowed"))
+        );
+    }
+
+    #[test]
+    fn trap_from_expectation() {
+        let mock = register_chain_extensions(());
+        mock.expect_another_method().trap(Trap("unavailable"));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(catch_trap(|| contract.call_another_method(1)), Err(Trap("unavailable")));
+    }
+
+    #[test]
+    fn trap_from_expectation_on_every_call() {
+        let mock = register_chain_extensions(());
+        let expectation = mock.expect_another_method().trap(Trap("unavailable"));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(catch_trap(|| contract.call_another_method(1)), Err(Trap("unavailable")));
+        assert_eq!(catch_trap(|| contract.call_another_method(2)), Err(Trap("unavailable")));
+        assert_eq!(expectation.call_count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected call to `another_method`")]
+    fn other_panics_are_propagated() {
+        let _mock = register_chain_extensions(());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        catch_trap(|| contract.call_another_method(1)).ok();
+    }
+}
+
+mod weight {
+    use obce::ink_lang::mock::{
+        catch_trap,
+        Weight,
+        OUT_OF_GAS,
+    };
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        #[obce(weight(expr = "Weight::from_parts(*val as u64, 10)"))]
+        fn method(&mut self, val: u32, another_val: u32) -> u32 {
+            val + another_val
+        }
+
+        #[obce(weight(expr = "Weight::from_parts(100, 0)"))]
+        fn another_method(&mut self, val: u32) -> u32;
+    }
+
+    #[test]
+    fn consumed_weight() {
+        let mock = register_chain_extensions(());
+        mock.expect_another_method().return_const(0);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_method(5, 0);
+        contract.call_another_method(0);
+        assert_eq!(mock.consumed_weight(), Weight::from_parts(105, 10));
+    }
+
+    #[test]
+    fn out_of_gas() {
+        let mock = register_chain_extensions(());
+        mock.set_gas_limit(Weight::from_parts(10, 25));
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(catch_trap(|| contract.call_method(5, 0)), Ok(5));
+        assert_eq!(catch_trap(|| contract.call_method(5, 0)), Ok(5));
+        assert_eq!(catch_trap(|| contract.call_method(0, 0)), Err(OUT_OF_GAS));
+        assert_eq!(mock.consumed_weight(), Weight::from_parts(10, 20));
+    }
+}
+
+mod environment {
+    use std::collections::BTreeMap;
+
+    use ink::env::{
+        test,
+        DefaultEnvironment,
+    };
+    use obce::ink_lang::mock::MockEnv;
+
+    #[derive(Default)]
+    pub struct State {
+        balances: BTreeMap<ink::primitives::AccountId, u32>,
+    }
+
+    #[obce::mock]
+    impl crate::Trait for State {
+        fn method(&mut self, val: u32, _: u32) -> u32 {
+            let balance = self.balances.entry(MockEnv::caller()).or_default();
+            *balance += val;
+            *balance
+        }
+
+        fn another_method(&mut self, _: u32) -> u32 {
+            MockEnv::block_number()
+        }
+    }
+
+    #[ink::test]
+    fn caller_dependent_state() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mock = register_chain_extensions(State::default());
+        MockEnv::capture_accounts();
+        let mut contract = crate::simple_contract::SimpleContract::new();
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(contract.call_method(10, 0), 10);
+
+        test::set_caller::<DefaultEnvironment>(accounts.bob);
+        assert_eq!(contract.call_method(5, 0), 5);
+
+        test::set_caller::<DefaultEnvironment>(accounts.alice);
+        assert_eq!(contract.call_method(1, 0), 11);
+
+        assert_eq!(mock.borrow().balances.len(), 2);
+    }
+
+    #[ink::test]
+    fn block_number() {
+        register_chain_extensions(State::default());
+        let mut contract = crate::simple_contract::SimpleContract::new();
+
+        assert_eq!(contract.call_another_method(0), 0);
+        test::advance_block::<DefaultEnvironment>();
+        assert_eq!(contract.call_another_method(0), 1);
+    }
+}
+
+mod per_callee {
+    use ink::env::{
+        test,
+        DefaultEnvironment,
+    };
+    use obce::ink_lang::mock::{
+        MockEnv,
+        PerCallee,
+    };
+
+    #[derive(Default)]
+    pub struct Balances(PerCallee<u32>);
+
+    #[obce::mock]
+    impl crate::Trait for Balances {
+        fn method(&mut self, val: u32, _: u32) -> u32 {
+            *self.0.current() += val;
+            *self.0.current()
+        }
+    }
+
+    #[ink::test]
+    fn separate_state() {
+        let accounts = test::default_accounts::<DefaultEnvironment>();
+        let mock = register_chain_extensions(Balances::default());
+        MockEnv::capture_accounts();
+        let mut token = crate::simple_contract::SimpleContract::new();
+        let mut dex = crate::simple_contract::SimpleContract::new();
+
+        test::set_callee::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(token.call_method(10, 0), 10);
+
+        test::set_callee::<DefaultEnvironment>(accounts.django);
+        assert_eq!(dex.call_method(1, 0), 1);
+
+        test::set_callee::<DefaultEnvironment>(accounts.charlie);
+        assert_eq!(token.call_method(10, 0), 20);
+
+        let balances = mock.borrow();
+        assert_eq!(balances.0.get(&accounts.charlie), Some(&20));
+        assert_eq!(balances.0.get(&accounts.django), Some(&1));
+    }
+}
+
+mod replay {
+    use obce::{
+        ink_lang::{
+            mock::catch_trap,
+            replay::{
+                Replay,
+                DIVERGED,
+            },
+        },
+        recording::{
+            RecordedCall,
+            RecordedRetVal,
+        },
+    };
+    use scale::Encode;
+
+    use crate::{
+        AnotherTrait,
+        Trait,
+    };
+
+    fn recorded_call(ext_id: u16, func_id: u16, input: Vec<u8>, output: u32) -> RecordedCall {
+        RecordedCall {
+            ext_id,
+            func_id,
+            input,
+            output: output.encode(),
+            ret_val: RecordedRetVal::Converging(0),
+        }
+    }
+
+    #[test]
+    fn replays_calls_in_order() {
+        let replay = Replay::register([
+            recorded_call(obce::id!(Trait), obce::id!(Trait::method), (1u32, 2u32).encode(), 10),
+            recorded_call(obce::id!(Trait), obce::id!(Trait::method), (1u32, 2u32).encode(), 20),
+            recorded_call(
+                obce::id!(AnotherTrait),
+                obce::id!(AnotherTrait::other_method),
+                3u32.encode(),
+                30,
+            ),
+        ]);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(contract.call_method(1, 2), 10);
+        assert_eq!(replay.remaining(), 2);
+        assert_eq!(contract.call_method(1, 2), 20);
+        assert_eq!(contract.call_another_trait_method(3), 30);
+
+        replay.assert_finished();
+    }
+
+    #[test]
+    #[should_panic(expected = "chain extension call #0 does not match the recording")]
+    fn mismatched_input() {
+        let _replay = Replay::register([recorded_call(
+            obce::id!(Trait),
+            obce::id!(Trait::method),
+            (1u32, 2u32).encode(),
+            10,
+        )]);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_method(1, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "all 1 recorded calls were already replayed")]
+    fn too_many_calls() {
+        let _replay = Replay::register([recorded_call(
+            obce::id!(Trait),
+            obce::id!(Trait::another_method),
+            1u32.encode(),
+            10,
+        )]);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        contract.call_another_method(1);
+        contract.call_another_method(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 recorded chain extension calls were not replayed")]
+    fn unfinished() {
+        let replay = Replay::register([recorded_call(
+            obce::id!(Trait),
+            obce::id!(Trait::another_method),
+            1u32.encode(),
+            10,
+        )]);
+
+        replay.assert_finished();
+    }
+
+    #[test]
+    fn diverging_call() {
+        let _replay = Replay::register([RecordedCall {
+            ext_id: obce::id!(Trait),
+            func_id: obce::id!(Trait::another_method),
+            input: 1u32.encode(),
+            output: vec![],
+            ret_val: RecordedRetVal::Diverging { flags: 1, data: vec![] },
+        }]);
+
+        let mut contract = crate::simple_contract::SimpleContract::new();
+        assert_eq!(catch_trap(|| contract.call_another_method(1)), Err(DIVERGED));
+    }
+}
+
+mod call_enum {
+    use scale::{
+        Decode,
+        Encode,
+    };
+
+    use crate::{
+        TestExtension,
+        Trait,
+        TraitCall,
+    };
+
+    #[obce::mock]
+    impl crate::Trait for () {
+        fn method(&mut self, val: u32, another_val: u32) -> u32 {
+            val + another_val
+        }
+    }
+
+    #[test]
+    fn func_id() {
+        assert_eq!(
+            TraitCall::Method(1, 2).func_id(),
+            (obce::id!(Trait) as u32) << 16 | obce::id!(Trait::method) as u32
+        );
+        assert_eq!(
+            TraitCall::AnotherMethod(1).func_id(),
+            (obce::id!(Trait) as u32) << 16 | obce::id!(Trait::another_method) as u32
+        );
+    }
+
+    #[test]
+    fn raw_input() {
+        let call = TraitCall::Method(1, 2);
+
+        assert_eq!(call.encode_input(), (1u32, 2u32).encode());
+        assert_eq!(TraitCall::decode_input(call.func_id(), &call.encode_input()), Ok(call));
+        assert!(TraitCall::decode_input(0, &[]).is_err());
+    }
+
+    #[test]
+    fn encoding() {
+        let call = TraitCall::FallibleMethod(5);
+
+        assert_eq!(TraitCall::decode(&mut &call.encode()[..]), Ok(call));
+    }
+
+    #[test]
+    fn dispatch() {
+        register_chain_extensions(());
+
+        assert_eq!(TraitCall::Method(1, 2).dispatch(&mut TestExtension), 3u32.encode());
+    }
+}
+
+mod reflection {
+    use obce::codegen::{
+        ExtensionDescription,
+        MetaType,
+        MethodInfo,
+        MethodTypes,
+    };
+
+    use crate::{
+        AnotherTrait,
+        Trait,
+    };
+
+    #[test]
+    fn extension_description() {
+        assert_eq!(<dyn Trait as ExtensionDescription>::NAME, "Trait");
+        assert_eq!(<dyn Trait as ExtensionDescription>::METHODS.len(), 3);
+
+        assert_eq!(
+            <dyn Trait as ExtensionDescription>::METHODS[0],
+            MethodInfo {
+                name: "method",
+                id: obce::id!(Trait::method),
+                legacy_ids: &[],
+                hash: obce::hash!(method),
+                args: &["val", "another_val"],
+                mutable: true,
+                docs: &["Sums two values.", "", "Used by most of the tests."],
+                signature: None,
+            }
+        );
+
+        let read_only = &<dyn AnotherTrait as ExtensionDescription>::METHODS[1];
+        assert_eq!(read_only.name, "read_only");
+        assert!(read_only.args.is_empty());
+        assert!(!read_only.mutable);
+    }
+
+    #[test]
+    fn method_types() {
+        let hash = obce::hash!(method);
+
+        assert_eq!(
+            <dyn Trait as MethodTypes>::input_type(hash),
+            Some(MetaType::new::<(u32, u32)>())
+        );
+        assert_eq!(
+            <dyn Trait as MethodTypes>::output_type(hash),
+            Some(MetaType::new::<u32>())
+        );
+        assert_eq!(<dyn Trait as MethodTypes>::input_type(0), None);
+    }
+
+    #[test]
+    #[cfg(feature = "metadata")]
+    fn metadata() {
+        use obce::metadata::{
+            ExtensionMetadata,
+            Metadata,
+            RetValMetadata,
+            METADATA_VERSION,
+        };
+
+        let metadata = <dyn Trait as ExtensionMetadata>::metadata();
+
+        assert_eq!(metadata.version, METADATA_VERSION);
+        assert_eq!(metadata.name, "Trait");
+        assert_eq!(metadata.id, 123);
+
+        let fallible = &metadata.methods[2];
+        assert_eq!(fallible.name, "fallible_method");
+        assert_eq!(fallible.func_id, 123 << 16 | obce::id!(Trait::fallible_method) as u32);
+        assert_eq!(
+            fallible.ret_vals,
+            vec![RetValMetadata {
+                variant: "WithRetVal".to_string(),
+                ret_val: 100,
+            }]
+        );
+
+        let output = metadata.types.resolve(fallible.output.unwrap()).unwrap();
+        assert_eq!(output.path.segments, vec!["Result"]);
+
+        assert!(metadata.methods[0].ret_vals.is_empty());
+        assert_eq!(Metadata::from_json(&metadata.to_json()).unwrap(), metadata);
+    }
+}
+
+mod associated_types {
+    use ink::primitives::AccountId;
+    use obce::codegen::ExtensionDescription;
+
+    use crate::{
+        AssetsTrait,
+        TestExtension,
+    };
+
+    #[obce::mock]
+    impl crate::AssetsTrait for () {
+        fn balance_of(&self, asset: u32, owner: AccountId) -> u128 {
+            if owner == AccountId::from([1; 32]) {
+                asset as u128 * 100
+            } else {
+                0
+            }
+        }
+    }
+
+    #[test]
+    fn environment_types() {
+        let mock = register_chain_extensions(());
+
+        assert_eq!(
+            AssetsTrait::balance_of(&TestExtension, 2, AccountId::from([1; 32])),
+            200
+        );
+        assert_eq!(AssetsTrait::balance_of(&TestExtension, 2, AccountId::from([2; 32])), 0);
+        assert_eq!(mock.balance_of_calls().len(), 2);
+    }
+
+    #[test]
+    fn ink_only_helpers() {
+        let mock = register_chain_extensions(());
+
+        assert!(AssetsTrait::has_balance(&TestExtension, 2, AccountId::from([1; 32])));
+        assert!(!AssetsTrait::has_balance(&TestExtension, 2, AccountId::from([2; 32])));
+        assert_eq!(mock.balance_of_calls().len(), 2);
+
+        let methods = <dyn AssetsTrait as ExtensionDescription>::METHODS;
+        assert_eq!(
+            methods.iter().map(|method| method.name).collect::<Vec<_>>(),
+            ["balance_of"]
+        );
+    }
+}
+
+mod borrowed_args {
+    use scale::Encode;
+
+    use crate::{
+        StorageExtension,
+        StorageTrait,
+        StorageTraitCall,
+    };
+
+    #[obce::mock]
+    impl crate::StorageTrait for () {
+        fn store(&mut self, key: String, value: Vec<u8>) -> u32 {
+            (key.len() + value.len()) as u32
+        }
+    }
+
+    #[test]
+    fn owned_inputs() {
+        let mock = register_chain_extensions(());
+
+        assert_eq!(StorageTrait::store(&mut StorageExtension, "key", &[1, 2, 3]), 6);
+        assert_eq!(mock.store_calls(), vec![("key".to_string(), vec![1, 2, 3])]);
+    }
+
+    #[test]
+    fn call_enum() {
+        let _mock = register_chain_extensions(());
+
+        let call = StorageTraitCall::Store("key".to_string(), vec![1, 2]);
+
+        assert_eq!(call.encode_input(), ("key", &[1u8, 2][..]).encode());
+        assert_eq!(call.dispatch(&mut StorageExtension), 5u32.encode());
+    }
+
+    #[test]
+    fn ink_struct() {
+        fn assert_storable<T>()
+        where
+            T: Default
+                + Copy
+                + scale::Encode
+                + scale::Decode
+                + scale_info::TypeInfo
+                + ink::storage::traits::StorageLayout,
+        {
+        }
+
+        assert_storable::<StorageExtension>();
+
+        let _mock = register_chain_extensions(());
+
+        assert_eq!(
+            <StorageExtension as ink::ChainExtensionInstance>::instantiate().store("key", &[1]),
+            4
+        );
+    }
+}
@@ -0,0 +1,14 @@
+#[obce::ink_lang::extension]
+pub struct RandExtension;
+
+mod other {
+    #[obce::ink_lang::extension]
+    pub struct RandExtension;
+}
+
+obce::ink_lang::environment!(
+    Env: obce::ink_lang::env::DefaultEnvironment,
+    extensions = [RandExtension, other::RandExtension],
+);
+
+fn main() {}
@@ -0,0 +1,6 @@
+obce::ink_lang::environment!(
+    Env: obce::ink_lang::env::DefaultEnvironment,
+    extensions = [],
+);
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[obce::ink_lang::extension]
+pub struct RandExtension;
+
+obce::ink_lang::environment!(
+    Env: obce::ink_lang::env::DefaultEnvironment,
+    extensions = [&'static RandExtension],
+);
+
+fn main() {}
@@ -0,0 +1,30 @@
+mod extensions {
+    #[obce::definition(ink_struct = "RandExtension")]
+    pub trait Rand {
+        fn fetch_random(&self, subject: [u8; 32]) -> [u8; 32];
+    }
+
+    #[obce::definition(ink_struct = "HTTPExtension")]
+    pub trait Http {
+        fn get(&self, url: u32) -> u32;
+    }
+}
+
+obce::ink_lang::environment!(
+    /// Environment with both extensions.
+    pub Env: obce::ink_lang::env::DefaultEnvironment,
+    extensions = [extensions::RandExtension, extensions::HTTPExtension],
+);
+
+#[allow(dead_code)]
+fn call_extensions() -> ([u8; 32], u32) {
+    use extensions::{
+        Http,
+        Rand,
+    };
+
+    let extensions = <Env as obce::ink_lang::env::Environment>::ChainExtension::default();
+    (extensions.rand().fetch_random([0; 32]), extensions.http().get(1))
+}
+
+fn main() {}
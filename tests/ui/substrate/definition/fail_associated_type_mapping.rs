@@ -0,0 +1,8 @@
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    type AssetId;
+
+    fn total_supply(&self, asset: Self::AssetId) -> u128;
+}
+
+fn main() {}
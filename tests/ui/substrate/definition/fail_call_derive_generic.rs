@@ -0,0 +1,6 @@
+#[obce::definition(call_derive(Debug))]
+pub trait ChainExtensionDefinition<T> {
+    fn extension_method(&self, val: T);
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+#[obce::definition]
+pub trait Trait {
+    #[obce(id = 123)]
+    fn extension_method(&self);
+
+    #[obce(id = 124, legacy_ids(123))]
+    fn another_extension_method(&self);
+}
+
+fn main() {}
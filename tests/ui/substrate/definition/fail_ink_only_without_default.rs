@@ -0,0 +1,9 @@
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    fn method(&self, val: u32) -> u32;
+
+    #[obce(ink_only)]
+    fn helper(&self, val: u32) -> u32;
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[obce::definition(ink_struct = "Extension")]
+pub trait ChainExtensionDefinition<T> {
+    fn extension_method(&self, val: T);
+}
+
+fn main() {}
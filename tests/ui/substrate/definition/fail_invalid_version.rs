@@ -0,0 +1,4 @@
+#[obce::definition(version = 0)]
+pub trait Trait {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    fn store(&mut self, #[obce(owned = "Vec<u8>")] value: Vec<u8>) -> u32;
+}
+
+fn main() {}
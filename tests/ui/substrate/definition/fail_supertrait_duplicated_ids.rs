@@ -0,0 +1,13 @@
+#[obce::definition(id = 1)]
+pub trait BaseDefinition {
+    #[obce(id = 1)]
+    fn base_method(&self) -> u32;
+}
+
+#[obce::definition(id = 1)]
+pub trait ChainExtensionDefinition: BaseDefinition {
+    #[obce(id = 1)]
+    fn extension_method(&mut self, val: u64) -> u64;
+}
+
+fn main() {}
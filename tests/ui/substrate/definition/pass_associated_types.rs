@@ -0,0 +1,15 @@
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    type AccountId;
+
+    type Balance;
+
+    #[obce(ink = "u32", substrate = "u32")]
+    type AssetId;
+
+    fn balance_of(&self, asset: Self::AssetId, owner: Self::AccountId) -> Self::Balance;
+
+    fn transfer(&mut self, asset: Self::AssetId, to: Self::AccountId, amount: Self::Balance) -> Option<Self::Balance>;
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    fn store(&mut self, key: &str, value: &[u8], flags: &(u8, bool)) -> u32;
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+/// Argument type that doesn't implement `Debug`, `Clone`, `PartialEq` or `Eq`.
+#[derive(scale::Encode, scale::Decode)]
+pub struct Payload {
+    pub value: u32,
+}
+
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    fn submit(&mut self, payload: Payload) -> u32;
+}
+
+fn main() {
+    let call = ChainExtensionDefinitionCall::Submit(Payload { value: 1 });
+    let input = call.encode_input();
+
+    assert!(ChainExtensionDefinitionCall::decode_input(call.func_id(), &input).is_ok());
+}
@@ -0,0 +1,14 @@
+#[obce::definition(call_derive(Debug, Clone, PartialEq, Eq))]
+pub trait ChainExtensionDefinition {
+    fn method(&mut self, val: u32, another_val: u64) -> u32;
+}
+
+fn main() {
+    let call = ChainExtensionDefinitionCall::Method(1, 2);
+    let input = call.encode_input();
+
+    assert_eq!(
+        ChainExtensionDefinitionCall::decode_input(call.func_id(), &input),
+        Ok(call.clone())
+    );
+}
@@ -0,0 +1,41 @@
+mod const_eq;
+
+use const_eq::*;
+
+use obce::{
+    codegen::ExtensionDescription,
+    id,
+};
+
+#[obce::definition(id_from_signature)]
+pub trait Trait {
+    fn method(&mut self, val: u32, data: Vec<u8>) -> u64;
+
+    fn another_method(&self);
+
+    #[obce(id = 1)]
+    fn explicit_method(&self, val: u32);
+
+    #[obce(id = "string_method")]
+    fn explicit_string_method(&self, val: u32);
+}
+
+#[obce::definition(id_from_signature)]
+pub trait Formatted {
+    fn method(&self, data: Vec<Vec<u8>>) -> u64;
+}
+
+fn main() {
+    // blake2b_256("method(u32,Vec<u8>)->u64")
+    assert_const_eq::<{ id!(Trait::method) }, 0x12f1>();
+    // blake2b_256("another_method()->()")
+    assert_const_eq::<{ id!(Trait::another_method) }, 0xe5de>();
+    assert_const_eq::<{ id!(Trait::explicit_method) }, 1>();
+    // blake2b_256("method(Vec<Vec<u8>>)->u64")
+    assert_const_eq::<{ id!(Formatted::method) }, 0xc983>();
+
+    let methods = <dyn Trait as ExtensionDescription>::METHODS;
+    assert_eq!(methods[0].signature, Some("method(u32,Vec<u8>)->u64"));
+    assert_eq!(methods[2].signature, None);
+    assert_eq!(methods[3].signature, None);
+}
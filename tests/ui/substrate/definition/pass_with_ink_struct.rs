@@ -0,0 +1,6 @@
+#[obce::definition(ink_struct = "Extension")]
+pub trait ChainExtensionDefinition {
+    fn extension_method(&self);
+}
+
+fn main() {}
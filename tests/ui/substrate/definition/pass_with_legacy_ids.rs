@@ -0,0 +1,24 @@
+mod const_eq;
+
+use const_eq::*;
+
+use obce::codegen::{
+    ExtensionDescription,
+    MethodDescription,
+};
+
+#[obce::definition(version = 2)]
+pub trait Trait {
+    #[obce(id = 2, legacy_ids(1, 3))]
+    fn extension_method(&self, val: u64);
+}
+
+fn main() {
+    assert_const_eq::<{ <dyn Trait as ExtensionDescription>::VERSION }, 2>();
+    assert_const_eq::<{ <dyn Trait as MethodDescription<{ obce::hash!(extension_method) }>>::ID }, 2>();
+
+    assert_eq!(
+        <dyn Trait as MethodDescription<{ obce::hash!(extension_method) }>>::LEGACY_IDS,
+        &[1, 3]
+    );
+}
@@ -0,0 +1,19 @@
+#[obce::definition(id = 1)]
+pub trait BaseDefinition {
+    #[obce(id = 1)]
+    fn base_method(&self) -> u32;
+}
+
+#[obce::definition(id = 2)]
+pub trait OtherDefinition {
+    #[obce(id = 1)]
+    fn other_method(&self, val: u64) -> u64;
+}
+
+#[obce::definition(id = 1)]
+pub trait ChainExtensionDefinition: BaseDefinition + OtherDefinition {
+    #[obce(id = 2)]
+    fn extension_method(&mut self, val: u64) -> u64;
+}
+
+fn main() {}
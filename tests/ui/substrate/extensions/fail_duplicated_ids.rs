@@ -0,0 +1,44 @@
+mod runtime;
+
+use obce::substrate::pallet_contracts::chain_extension::{
+    ChainExtension,
+    Environment,
+    Ext,
+    InitState,
+    RegisteredChainExtension,
+    Result,
+    RetVal,
+};
+use runtime::Runtime;
+
+#[derive(Default)]
+pub struct First;
+
+impl ChainExtension<Runtime> for First {
+    fn call<E: Ext<T = Runtime>>(&mut self, _: Environment<E, InitState>) -> Result<RetVal> {
+        Ok(RetVal::Converging(0))
+    }
+}
+
+impl RegisteredChainExtension<Runtime> for First {
+    const ID: u16 = 1;
+}
+
+#[derive(Default)]
+pub struct Second;
+
+impl ChainExtension<Runtime> for Second {
+    fn call<E: Ext<T = Runtime>>(&mut self, _: Environment<E, InitState>) -> Result<RetVal> {
+        Ok(RetVal::Converging(0))
+    }
+}
+
+impl RegisteredChainExtension<Runtime> for Second {
+    const ID: u16 = 1;
+}
+
+type RuntimeExtensions = obce::substrate::extensions!(Runtime; First, Second);
+
+fn main() {
+    let _ = RuntimeExtensions::default();
+}
@@ -0,0 +1,34 @@
+use obce::substrate::{
+    frame_system::Config as SysConfig,
+    pallet_contracts::Config as ContractConfig,
+    BalanceOf,
+    ChainExtensionEnvironment,
+    ExtensionContext
+};
+
+pub struct ChainExtension;
+
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    type AccountId;
+
+    type Balance;
+
+    #[obce(ink = "u32", substrate = "u32")]
+    type AssetId;
+
+    fn balance_of(&self, asset: Self::AssetId, owner: Self::AccountId) -> Self::Balance;
+}
+
+#[obce::implementation]
+impl<'a, E, T, Env> ChainExtensionDefinition<T> for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn balance_of(&self, _asset: u32, _owner: T::AccountId) -> BalanceOf<T> {
+        Default::default()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+use obce::substrate::{
+    frame_system::Config as SysConfig,
+    pallet_contracts::Config as ContractConfig,
+    sp_core::ConstU32,
+    sp_runtime::BoundedVec,
+    ChainExtensionEnvironment,
+    ExtensionContext
+};
+
+pub struct ChainExtension;
+
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    fn store(
+        &mut self,
+        key: &str,
+        #[obce(owned = "obce::substrate::sp_runtime::BoundedVec<u8, obce::substrate::sp_core::ConstU32<64>>")]
+        value: &[u8],
+    ) -> u32;
+}
+
+#[obce::implementation]
+impl<'a, E, T, Env> ChainExtensionDefinition for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn store(&mut self, key: String, value: BoundedVec<u8, ConstU32<64>>) -> u32 {
+        (key.len() + value.len()) as u32
+    }
+}
+
+fn main() {}
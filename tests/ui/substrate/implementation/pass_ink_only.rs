@@ -0,0 +1,31 @@
+use obce::substrate::{
+    frame_system::Config as SysConfig,
+    pallet_contracts::Config as ContractConfig,
+    ChainExtensionEnvironment,
+    ExtensionContext
+};
+
+pub struct ChainExtension;
+
+#[obce::definition]
+pub trait ChainExtensionDefinition {
+    fn method(&self, val: u32) -> u32;
+
+    #[obce(ink_only)]
+    fn double_method(&self, val: u32) -> u32 {
+        self.method(val) + self.method(val)
+    }
+}
+
+#[obce::implementation]
+impl<'a, E, T, Env> ChainExtensionDefinition for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn method(&self, val: u32) -> u32 {
+        val
+    }
+}
+
+fn main() {}
@@ -0,0 +1,49 @@
+use obce::substrate::{
+    frame_support::dispatch::Weight,
+    frame_system::Config as SysConfig,
+    pallet_contracts::Config as ContractConfig,
+    sp_runtime::traits::StaticLookup,
+    ChainExtensionEnvironment,
+    ExtensionContext
+};
+
+#[obce::error]
+pub enum Error {
+    #[obce(ret_val = "100")]
+    One,
+}
+
+pub struct ChainExtension;
+
+#[obce::definition(version = 2)]
+pub trait ChainExtensionDefinition {
+    #[obce(id = "extension_method_v2", legacy_ids("extension_method"))]
+    fn extension_method(&mut self, val: u64, another_val: u32) -> Result<u64, Error>;
+
+    #[obce(id = "another_method_v2", legacy_ids("another_method"))]
+    fn another_method(&self) -> u32;
+}
+
+#[obce::implementation]
+impl<'a, E, T, Env> ChainExtensionDefinition for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    <<T as SysConfig>::Lookup as StaticLookup>::Source: From<<T as SysConfig>::AccountId>,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    #[obce(ret_val)]
+    fn extension_method(&mut self, val: u64, another_val: u32) -> Result<u64, Error> {
+        Ok(val + another_val as u64)
+    }
+
+    #[obce(ret_val, legacy(id = "extension_method"), weight(expr = "Weight::from_parts(*val, 0)"))]
+    fn extension_method_v1(&mut self, val: u64) -> Result<u64, Error> {
+        self.extension_method(val, 0)
+    }
+
+    fn another_method(&self) -> u32 {
+        123
+    }
+}
+
+fn main() {}
@@ -0,0 +1,57 @@
+use obce::substrate::{
+    frame_system::Config as SysConfig,
+    mock::Recorder,
+    pallet_contracts::{
+        chain_extension::RegisteredChainExtension,
+        Config as ContractConfig,
+    },
+    ChainExtensionEnvironment,
+    ExtensionContext,
+};
+
+pub struct ChainExtension;
+
+#[obce::definition(id = 1)]
+pub trait BaseDefinition {
+    #[obce(id = 1)]
+    fn base_method(&self) -> u32;
+}
+
+#[obce::definition(id = 2)]
+pub trait ChainExtensionDefinition: BaseDefinition {
+    #[obce(id = 2)]
+    fn extension_method(&mut self, val: u64) -> u64;
+}
+
+#[obce::implementation(supertrait)]
+impl<'a, E, T, Env> BaseDefinition for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn base_method(&self) -> u32 {
+        123
+    }
+}
+
+#[obce::implementation]
+impl<'a, E, T, Env> ChainExtensionDefinition for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn extension_method(&mut self, val: u64) -> u64 {
+        val + self.base_method() as u64
+    }
+}
+
+fn assert_registered<T: ContractConfig, C: RegisteredChainExtension<T>>() -> u16 {
+    C::ID
+}
+
+#[allow(dead_code)]
+fn recorder_is_registered<T: SysConfig + ContractConfig>() -> u16 {
+    assert_registered::<T, Recorder<ChainExtension>>()
+}
+
+fn main() {}
@@ -0,0 +1,44 @@
+use obce::substrate::{
+    frame_system::Config as SysConfig,
+    pallet_contracts::Config as ContractConfig,
+    ChainExtensionEnvironment,
+    ExtensionContext
+};
+
+pub struct ChainExtension;
+
+#[obce::definition(id = 1)]
+pub trait BaseDefinition {
+    #[obce(id = 1)]
+    fn base_method(&self) -> u32;
+}
+
+#[obce::definition(id = 1)]
+pub trait ChainExtensionDefinition: BaseDefinition {
+    #[obce(id = 2)]
+    fn extension_method(&mut self, val: u64) -> u64;
+}
+
+#[obce::implementation(supertrait)]
+impl<'a, E, T, Env> BaseDefinition for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn base_method(&self) -> u32 {
+        123
+    }
+}
+
+#[obce::implementation]
+impl<'a, E, T, Env> ChainExtensionDefinition for ExtensionContext<'a, E, T, Env, ChainExtension>
+where
+    T: SysConfig + ContractConfig,
+    Env: ChainExtensionEnvironment<E, T>,
+{
+    fn extension_method(&mut self, val: u64) -> u64 {
+        val + self.base_method() as u64
+    }
+}
+
+fn main() {}